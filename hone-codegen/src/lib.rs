@@ -0,0 +1,158 @@
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Text};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub sql_type: String,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+}
+
+// Tables of a SQLite database sorted by name, columns in declaration order
+pub fn read_tables(conn: &SqliteConnection) -> QueryResult<Vec<TableInfo>> {
+    let names = sql::<Text>("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name").load::<String>(conn)?;
+
+    names
+        .into_iter()
+        .map(|name| {
+            let query = format!(
+                "SELECT name, type, \"notnull\", pk > 0 FROM pragma_table_info('{}') ORDER BY cid",
+                name.replace('\'', "''")
+            );
+            let columns = sql::<(Text, Text, Bool, Bool)>(&query)
+                .load::<(String, String, bool, bool)>(conn)?
+                .into_iter()
+                .map(|(name, sql_type, not_null, primary_key)| ColumnInfo {
+                    name,
+                    sql_type,
+                    not_null,
+                    primary_key,
+                })
+                .collect();
+
+            Ok(TableInfo { name, columns })
+        })
+        .collect()
+}
+
+// Rust type of a declared column type, following SQLite's affinity rules
+pub fn rust_type(sql_type: &str) -> &'static str {
+    let t = sql_type.to_ascii_uppercase();
+
+    if t.contains("INT") {
+        "i64"
+    } else if t.contains("CHAR") || t.contains("CLOB") || t.contains("TEXT") || t.contains("DATE") || t.contains("TIME") {
+        "String"
+    } else if t.contains("BLOB") || t.is_empty() {
+        "Vec<u8>"
+    } else if t.contains("BOOL") {
+        "bool"
+    } else {
+        "f64"
+    }
+}
+
+// `user_accounts` -> `UserAccounts`
+pub fn struct_name(table: &str) -> String {
+    table
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut cs = s.chars();
+            match cs.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + cs.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn is_ident(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+        "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+        "unsafe", "use", "where", "while",
+    ];
+
+    let mut cs = name.chars();
+    let head = cs.next().is_some_and(|c| c == '_' || c.is_ascii_alphabetic());
+
+    head && cs.all(|c| c == '_' || c.is_ascii_alphanumeric()) && name != "_" && !KEYWORDS.contains(&name)
+}
+
+fn generate_table(t: &TableInfo) -> String {
+    // hone_model! takes the table name as an identifier
+    if !is_ident(&t.name) {
+        return format!("// skipped table {:?}: not a Rust identifier\n", t.name);
+    }
+
+    let model = struct_name(&t.name);
+    let (columns, skipped): (Vec<_>, Vec<_>) = t.columns.iter().partition(|c| is_ident(&c.name));
+
+    let mut out = String::new();
+
+    for c in skipped.iter() {
+        out += &format!("// skipped column {}.{:?}: not a Rust identifier\n", t.name, c.name);
+    }
+
+    if columns.is_empty() {
+        return out + &format!("// skipped table {:?}: no columns\n", t.name);
+    }
+
+    // Users of the generated code rarely read every field
+    out += "#[allow(dead_code)]\n";
+    out += "#[derive(Debug, Default, Clone)]\n";
+    out += &format!("pub struct {} {{\n", model);
+    for c in columns.iter() {
+        let ty = rust_type(&c.sql_type);
+        // SQLite allows NULL in primary keys other than INTEGER PRIMARY KEY
+        if c.not_null || (c.primary_key && ty == "i64") {
+            out += &format!("    pub {}: {},\n", c.name, ty);
+        } else {
+            out += &format!("    pub {}: Option<{}>,\n", c.name, ty);
+        }
+    }
+    out += "}\n\n";
+
+    out += &format!("hone_model!(\n    {},\n    {}", model, t.name);
+    for c in columns.iter() {
+        out += &format!(",\n    {} => {}", c.name, rust_type(&c.sql_type));
+    }
+    out += "\n);\n";
+
+    out += &format!("hone_entity!(\n    {},\n    {}", model, t.name);
+    for c in columns.iter() {
+        out += &format!(",\n    {}", c.name);
+    }
+    out += "\n);\n";
+
+    out
+}
+
+// One file for all tables. The output only depends on the schema, so
+// regenerating it gives a clean diff.
+pub fn generate(tables: &[TableInfo]) -> String {
+    let mut out = String::from(
+        "// @generated by hone-codegen, do not edit by hand.\n\n\
+         use std::rc::Rc;\n\n\
+         use hone::entity::*;\n\
+         use hone::query::*;\n\
+         use hone::types::*;\n\
+         use hone::{hone_entity, hone_model};\n",
+    );
+
+    for t in tables.iter() {
+        out += "\n";
+        out += &generate_table(t);
+    }
+
+    out
+}
@@ -0,0 +1,34 @@
+use std::fs;
+use std::process;
+
+use diesel::prelude::*;
+
+// hone-codegen <database.sqlite> [output.rs]
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("usage: {} <database.sqlite> [output.rs]", args[0]);
+        process::exit(2);
+    }
+
+    let conn = SqliteConnection::establish(&args[1]).unwrap_or_else(|e| {
+        eprintln!("error: {}: {}", args[1], e);
+        process::exit(1);
+    });
+
+    let tables = hone_codegen::read_tables(&conn).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+
+    let code = hone_codegen::generate(&tables);
+
+    match args.get(2) {
+        Some(path) => fs::write(path, code).unwrap_or_else(|e| {
+            eprintln!("error: {}: {}", path, e);
+            process::exit(1);
+        }),
+        None => print!("{}", code),
+    }
+}
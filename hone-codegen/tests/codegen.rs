@@ -0,0 +1,85 @@
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+
+use hone_codegen::*;
+
+mod generated {
+    include!("fixtures/library.rs");
+}
+
+fn establish_connection() -> SqliteConnection {
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE users (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 email TEXT NOT NULL,
+                 avatar BLOB,
+                 score REAL,
+                 active BOOLEAN NOT NULL DEFAULT 1,
+                 \"type\" TEXT
+             );
+             CREATE TABLE library_books (book_id INTEGER NOT NULL, title VARCHAR(200), published DATETIME);",
+        )
+        .unwrap();
+
+    connection
+}
+
+#[test]
+fn test_read_tables() {
+    let tables = read_tables(&establish_connection()).unwrap();
+
+    assert_eq!(tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["library_books", "users"]);
+    assert_eq!(
+        tables[1].columns[0],
+        ColumnInfo {
+            name: "id".to_string(),
+            sql_type: "INTEGER".to_string(),
+            not_null: false,
+            primary_key: true,
+        }
+    );
+}
+
+#[test]
+fn test_rust_type() {
+    assert_eq!(rust_type("BIGINT"), "i64");
+    assert_eq!(rust_type("varchar(20)"), "String");
+    assert_eq!(rust_type(""), "Vec<u8>");
+    assert_eq!(rust_type("DOUBLE"), "f64");
+    assert_eq!(rust_type("BOOLEAN"), "bool");
+    assert_eq!(rust_type("DECIMAL(10,2)"), "f64");
+    assert_eq!(struct_name("library_books"), "LibraryBooks");
+}
+
+#[test]
+fn test_generate() {
+    let tables = read_tables(&establish_connection()).unwrap();
+    let code = generate(&tables);
+
+    assert_eq!(code, include_str!("fixtures/library.rs"));
+    assert_eq!(code, generate(&read_tables(&establish_connection()).unwrap()));
+}
+
+#[test]
+fn test_generated_entities() {
+    use hone::expression::*;
+    use hone::query::*;
+
+    let a = Query::<generated::Users>::from_by(|q, u| {
+        let q = q.where_(eq_(u.email(), val_("a@b.c".to_string())));
+        q.return_((u.id(), u.avatar()))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT users.id, users.avatar FROM users WHERE (users.email = 'a@b.c')".to_string()
+    );
+
+    let b = generated::LibraryBooks {
+        book_id: 1,
+        ..Default::default()
+    };
+    assert_eq!(b.title, None);
+}
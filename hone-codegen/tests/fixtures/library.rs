@@ -0,0 +1,61 @@
+// @generated by hone-codegen, do not edit by hand.
+
+use std::rc::Rc;
+
+use hone::entity::*;
+use hone::query::*;
+use hone::types::*;
+use hone::{hone_entity, hone_model};
+
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone)]
+pub struct LibraryBooks {
+    pub book_id: i64,
+    pub title: Option<String>,
+    pub published: Option<String>,
+}
+
+hone_model!(
+    LibraryBooks,
+    library_books,
+    book_id => i64,
+    title => String,
+    published => String
+);
+hone_entity!(
+    LibraryBooks,
+    library_books,
+    book_id,
+    title,
+    published
+);
+
+// skipped column users."type": not a Rust identifier
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone)]
+pub struct Users {
+    pub id: i64,
+    pub email: String,
+    pub avatar: Option<Vec<u8>>,
+    pub score: Option<f64>,
+    pub active: bool,
+}
+
+hone_model!(
+    Users,
+    users,
+    id => i64,
+    email => String,
+    avatar => Vec<u8>,
+    score => f64,
+    active => bool
+);
+hone_entity!(
+    Users,
+    users,
+    id,
+    email,
+    avatar,
+    score,
+    active
+);
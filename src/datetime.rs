@@ -0,0 +1,416 @@
+use std::fmt;
+
+use crate::dialect::{dialect, Dialect};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatePart {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl fmt::Display for DatePart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let part = match self {
+            DatePart::Year => "YEAR",
+            DatePart::Month => "MONTH",
+            DatePart::Day => "DAY",
+            DatePart::Hour => "HOUR",
+            DatePart::Minute => "MINUTE",
+            DatePart::Second => "SECOND",
+        };
+        write!(f, "{}", part)
+    }
+}
+
+impl DatePart {
+    // strftime() pattern extracting this part on SQLite
+    pub fn sqlite_format(self) -> &'static str {
+        match self {
+            DatePart::Year => "%Y",
+            DatePart::Month => "%m",
+            DatePart::Day => "%d",
+            DatePart::Hour => "%H",
+            DatePart::Minute => "%M",
+            DatePart::Second => "%S",
+        }
+    }
+
+    // strftime() / DATE_FORMAT() pattern truncating a timestamp to this part
+    pub fn truncate_format(self, d: Dialect) -> &'static str {
+        match (d, self) {
+            (_, DatePart::Year) => "%Y-01-01 00:00:00",
+            (_, DatePart::Month) => "%Y-%m-01 00:00:00",
+            (_, DatePart::Day) => "%Y-%m-%d 00:00:00",
+            (_, DatePart::Hour) => "%Y-%m-%d %H:00:00",
+            (Dialect::Mysql, DatePart::Minute) => "%Y-%m-%d %H:%i:00",
+            (Dialect::Mysql, DatePart::Second) => "%Y-%m-%d %H:%i:%s",
+            (_, DatePart::Minute) => "%Y-%m-%d %H:%M:00",
+            (_, DatePart::Second) => "%Y-%m-%d %H:%M:%S",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemporalKind {
+    Date,
+    Time,
+    DateTime,
+}
+
+// Expr (Value a) where a is a date, a time or a timestamp
+pub trait Temporal {
+    fn kind() -> TemporalKind;
+}
+
+// Expr (Value a) where a is a calendar date
+pub trait HasDate: Temporal {}
+
+// Expr (Value a) where a is a time span
+pub trait HasInterval {}
+
+pub fn date_literal(year: i32, month: u32, day: u32) -> String {
+    typed_literal("DATE", format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+pub fn time_literal(hour: u32, minute: u32, second: u32, nano: u32) -> String {
+    typed_literal("TIME", format_time(hour, minute, second, nano))
+}
+
+pub fn datetime_literal(date: (i32, u32, u32), time: (u32, u32, u32, u32), offset: Option<i32>) -> String {
+    let (year, month, day) = date;
+    let (hour, minute, second, nano) = time;
+    let s = format!("{:04}-{:02}-{:02} {}", year, month, day, format_time(hour, minute, second, nano));
+
+    match offset {
+        None => typed_literal("TIMESTAMP", s),
+        Some(offset) => {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let offset = offset.abs();
+            let s = format!("{}{}{:02}:{:02}", s, sign, offset / 3600, offset % 3600 / 60);
+
+            match dialect() {
+                Dialect::Postgres => format!("TIMESTAMPTZ '{}'", s),
+                _ => typed_literal("TIMESTAMP WITH TIME ZONE", s),
+            }
+        }
+    }
+}
+
+pub fn interval_literal(seconds: i64, nanos: i32) -> String {
+    let total = i128::from(seconds) * 1_000_000_000 + i128::from(nanos);
+    let sign = if total < 0 { "-" } else { "" };
+    let whole = total.abs() / 1_000_000_000;
+    let frac = total.abs() % 1_000_000_000;
+
+    let value = if frac == 0 {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, format!("{:09}", frac).trim_end_matches('0'))
+    };
+
+    match dialect() {
+        Dialect::Sqlite => format!("'{} seconds'", value),
+        Dialect::Postgres => format!("INTERVAL '{} seconds'", value),
+        Dialect::Mysql if frac == 0 => format!("INTERVAL {} SECOND", value),
+        Dialect::Mysql => format!("INTERVAL {} MICROSECOND", total / 1_000),
+        Dialect::Standard => format!("INTERVAL '{}' SECOND", value),
+    }
+}
+
+pub fn type_name(kind: TemporalKind, tz: bool, d: Dialect) -> &'static str {
+    match (d, kind) {
+        (Dialect::Sqlite, _) => "TEXT",
+        (_, TemporalKind::Date) => "DATE",
+        (_, TemporalKind::Time) => "TIME",
+        (Dialect::Mysql, TemporalKind::DateTime) => "DATETIME",
+        (Dialect::Postgres, TemporalKind::DateTime) if tz => "TIMESTAMPTZ",
+        (_, TemporalKind::DateTime) if tz => "TIMESTAMP WITH TIME ZONE",
+        (_, TemporalKind::DateTime) => "TIMESTAMP",
+    }
+}
+
+fn typed_literal(kind: &str, s: String) -> String {
+    match dialect() {
+        Dialect::Sqlite => format!("'{}'", s),
+        _ => format!("{} '{}'", kind, s),
+    }
+}
+
+fn format_time(hour: u32, minute: u32, second: u32, nano: u32) -> String {
+    let s = format!("{:02}:{:02}:{:02}", hour, minute, second);
+
+    if nano == 0 {
+        return s;
+    }
+
+    format!("{}.{}", s, format!("{:09}", nano).trim_end_matches('0'))
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+    use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike};
+
+    use super::*;
+    use crate::types::{SqlLiteral, SqlType, ToLiteral};
+
+    impl ToLiteral for NaiveDate {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for NaiveTime {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for NaiveDateTime {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl<Tz: TimeZone> ToLiteral for DateTime<Tz> {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for Duration {}
+
+    impl SqlLiteral for NaiveDate {
+        fn sql_literal(&self) -> String {
+            date_literal(self.year(), self.month(), self.day())
+        }
+    }
+
+    impl SqlLiteral for NaiveTime {
+        fn sql_literal(&self) -> String {
+            time_literal(self.hour(), self.minute(), self.second(), self.nanosecond())
+        }
+    }
+
+    impl SqlLiteral for NaiveDateTime {
+        fn sql_literal(&self) -> String {
+            let date = (self.year(), self.month(), self.day());
+            let time = (self.hour(), self.minute(), self.second(), self.nanosecond());
+
+            datetime_literal(date, time, None)
+        }
+    }
+
+    impl<Tz: TimeZone> SqlLiteral for DateTime<Tz> {
+        fn sql_literal(&self) -> String {
+            if dialect() == Dialect::Mysql {
+                return self.naive_utc().sql_literal();
+            }
+
+            let local = self.naive_local();
+            let offset = self.offset().fix().local_minus_utc();
+
+            let date = (local.year(), local.month(), local.day());
+            let time = (local.hour(), local.minute(), local.second(), local.nanosecond());
+
+            datetime_literal(date, time, Some(offset))
+        }
+    }
+
+    impl SqlLiteral for Duration {
+        fn sql_literal(&self) -> String {
+            let seconds = self.num_seconds();
+            let nanos = (*self - Duration::seconds(seconds)).num_nanoseconds().unwrap_or(0);
+
+            interval_literal(seconds, nanos as i32)
+        }
+    }
+
+    impl SqlType for NaiveDate {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::Date, false, d)
+        }
+    }
+
+    impl SqlType for NaiveTime {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::Time, false, d)
+        }
+    }
+
+    impl SqlType for NaiveDateTime {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::DateTime, false, d)
+        }
+    }
+
+    impl<Tz: TimeZone> SqlType for DateTime<Tz> {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::DateTime, true, d)
+        }
+    }
+
+    impl Temporal for NaiveDate {
+        fn kind() -> TemporalKind {
+            TemporalKind::Date
+        }
+    }
+
+    impl Temporal for NaiveTime {
+        fn kind() -> TemporalKind {
+            TemporalKind::Time
+        }
+    }
+
+    impl Temporal for NaiveDateTime {
+        fn kind() -> TemporalKind {
+            TemporalKind::DateTime
+        }
+    }
+
+    impl<Tz: TimeZone> Temporal for DateTime<Tz> {
+        fn kind() -> TemporalKind {
+            TemporalKind::DateTime
+        }
+    }
+
+    impl HasDate for NaiveDate {}
+
+    impl HasInterval for Duration {}
+}
+
+#[cfg(feature = "time")]
+mod time_impl {
+    use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
+
+    use super::*;
+    use crate::types::{SqlLiteral, SqlType, ToLiteral};
+
+    fn date_parts(d: Date) -> (i32, u32, u32) {
+        (d.year(), u32::from(u8::from(d.month())), u32::from(d.day()))
+    }
+
+    fn time_parts(t: Time) -> (u32, u32, u32, u32) {
+        (u32::from(t.hour()), u32::from(t.minute()), u32::from(t.second()), t.nanosecond())
+    }
+
+    impl ToLiteral for Date {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for Time {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for PrimitiveDateTime {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for OffsetDateTime {
+        fn to_literal<A: fmt::Display>(v: &A) -> String {
+            format!("'{}'", v)
+        }
+    }
+
+    impl ToLiteral for Duration {}
+
+    impl SqlLiteral for Date {
+        fn sql_literal(&self) -> String {
+            let (year, month, day) = date_parts(*self);
+            date_literal(year, month, day)
+        }
+    }
+
+    impl SqlLiteral for Time {
+        fn sql_literal(&self) -> String {
+            let (hour, minute, second, nano) = time_parts(*self);
+            time_literal(hour, minute, second, nano)
+        }
+    }
+
+    impl SqlLiteral for PrimitiveDateTime {
+        fn sql_literal(&self) -> String {
+            datetime_literal(date_parts(self.date()), time_parts(self.time()), None)
+        }
+    }
+
+    impl SqlLiteral for OffsetDateTime {
+        fn sql_literal(&self) -> String {
+            if dialect() == Dialect::Mysql {
+                let utc = self.to_offset(time::UtcOffset::UTC);
+                return datetime_literal(date_parts(utc.date()), time_parts(utc.time()), None);
+            }
+
+            let offset = self.offset().whole_seconds();
+
+            datetime_literal(date_parts(self.date()), time_parts(self.time()), Some(offset))
+        }
+    }
+
+    impl SqlLiteral for Duration {
+        fn sql_literal(&self) -> String {
+            interval_literal(self.whole_seconds(), self.subsec_nanoseconds())
+        }
+    }
+
+    impl SqlType for Date {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::Date, false, d)
+        }
+    }
+
+    impl SqlType for Time {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::Time, false, d)
+        }
+    }
+
+    impl SqlType for PrimitiveDateTime {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::DateTime, false, d)
+        }
+    }
+
+    impl SqlType for OffsetDateTime {
+        fn type_name(d: Dialect) -> &'static str {
+            type_name(TemporalKind::DateTime, true, d)
+        }
+    }
+
+    impl Temporal for Date {
+        fn kind() -> TemporalKind {
+            TemporalKind::Date
+        }
+    }
+
+    impl Temporal for Time {
+        fn kind() -> TemporalKind {
+            TemporalKind::Time
+        }
+    }
+
+    impl Temporal for PrimitiveDateTime {
+        fn kind() -> TemporalKind {
+            TemporalKind::DateTime
+        }
+    }
+
+    impl Temporal for OffsetDateTime {
+        fn kind() -> TemporalKind {
+            TemporalKind::DateTime
+        }
+    }
+
+    impl HasDate for Date {}
+
+    impl HasInterval for Duration {}
+}
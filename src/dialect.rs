@@ -0,0 +1,201 @@
+use std::cell::Cell;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Expressions are rendered when they are built, so the dialect is kept
+// per thread and consulted by every constructor that needs it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Dialect {
+    #[default]
+    Standard,
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+thread_local! {
+    static DIALECT: Cell<Dialect> = Cell::new(Dialect::default());
+}
+
+pub fn dialect() -> Dialect {
+    DIALECT.with(|d| d.get())
+}
+
+pub fn set_dialect(d: Dialect) {
+    DIALECT.with(|c| c.set(d));
+}
+
+pub fn with_dialect<F, R>(d: Dialect, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = dialect();
+    set_dialect(d);
+    let r = f();
+    set_dialect(prev);
+    r
+}
+
+impl Dialect {
+    pub fn quote_style(self) -> QuoteStyle {
+        match self {
+            Dialect::Mysql => QuoteStyle::Backtick,
+            _ => QuoteStyle::DoubleQuote,
+        }
+    }
+}
+
+// When table and column names are quoted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotePolicy {
+    #[default]
+    Never,
+    WhenNeeded,
+    Always,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    DoubleQuote, // "x"
+    Backtick,    // `x`
+    Bracket,     // [x]
+}
+
+thread_local! {
+    static QUOTE_POLICY: Cell<QuotePolicy> = Cell::new(QuotePolicy::default());
+    static QUOTE_STYLE: Cell<Option<QuoteStyle>> = const { Cell::new(None) };
+    static UNQUALIFIED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn quote_policy() -> QuotePolicy {
+    QUOTE_POLICY.with(|p| p.get())
+}
+
+pub fn set_quote_policy(p: QuotePolicy) {
+    QUOTE_POLICY.with(|c| c.set(p));
+}
+
+pub fn with_quote_policy<F, R>(p: QuotePolicy, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = quote_policy();
+    set_quote_policy(p);
+    let r = f();
+    set_quote_policy(prev);
+    r
+}
+
+// Columns render without their schema and table, e.g. for DDL where
+// `User.email` has to be written `email`
+pub fn unqualified_columns() -> bool {
+    UNQUALIFIED.with(|u| u.get())
+}
+
+pub fn with_unqualified_columns<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = unqualified_columns();
+    UNQUALIFIED.with(|u| u.set(true));
+    let r = f();
+    UNQUALIFIED.with(|u| u.set(prev));
+    r
+}
+
+// Quote style of the current dialect unless overridden with `set_quote_style`
+pub fn quote_style() -> QuoteStyle {
+    QUOTE_STYLE.with(|s| s.get()).unwrap_or_else(|| dialect().quote_style())
+}
+
+pub fn set_quote_style(s: Option<QuoteStyle>) {
+    QUOTE_STYLE.with(|c| c.set(s));
+}
+
+pub fn quote_ident(name: &str) -> String {
+    let quote = match quote_policy() {
+        QuotePolicy::Never => false,
+        QuotePolicy::WhenNeeded => needs_quote(name),
+        QuotePolicy::Always => name != "*",
+    };
+
+    if !quote {
+        return name.to_string();
+    }
+
+    match quote_style() {
+        QuoteStyle::DoubleQuote => format!("\"{}\"", name.replace('"', "\"\"")),
+        QuoteStyle::Backtick => format!("`{}`", name.replace('`', "``")),
+        QuoteStyle::Bracket => format!("[{}]", name.replace(']', "]]")),
+    }
+}
+
+// Parts of a rendered, possibly qualified name with the quotes undone,
+// e.g. `"main"."a.b"` is `["main", "a.b"]`
+pub fn unquote_ident(sql: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut chars = sql.chars().peekable();
+
+    while chars.peek().is_some() {
+        let close = match chars.peek() {
+            Some('"') => Some('"'),
+            Some('`') => Some('`'),
+            Some('[') => Some(']'),
+            _ => None,
+        };
+        let mut part = String::new();
+
+        match close {
+            Some(close) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == close && chars.next_if_eq(&close).is_none() {
+                        break;
+                    }
+                    part.push(c);
+                }
+                chars.next_if_eq(&'.');
+            }
+            None => {
+                for c in chars.by_ref() {
+                    if c == '.' {
+                        break;
+                    }
+                    part.push(c);
+                }
+            }
+        }
+
+        parts.push(part);
+    }
+
+    parts
+}
+
+fn needs_quote(name: &str) -> bool {
+    if name == "*" {
+        return false;
+    }
+
+    let plain = name.chars().enumerate().all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    // PostgreSQL folds unquoted names to lower case
+    let folded = dialect() == Dialect::Postgres && name.chars().any(|c| c.is_ascii_uppercase());
+
+    name.is_empty() || !plain || folded || is_reserved(name)
+}
+
+pub fn is_reserved(name: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "CHECK", "COLUMN", "CONSTRAINT", "CREATE", "CROSS",
+        "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "CURRENT_USER", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE", "END",
+        "EXCEPT", "EXISTS", "FALSE", "FETCH", "FOR", "FOREIGN", "FROM", "FULL", "GRANT", "GROUP", "HAVING", "IN", "INDEX", "INNER", "INSERT",
+        "INTERSECT", "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE", "LIMIT", "NATURAL", "NOT", "NULL", "OFFSET", "ON", "OR", "ORDER", "OUTER",
+        "PRIMARY", "REFERENCES", "RIGHT", "ROW", "SELECT", "SESSION_USER", "SET", "SOME", "TABLE", "THEN", "TO", "TRUE", "UNION", "UNIQUE",
+        "UPDATE", "USER", "USING", "VALUES", "WHEN", "WHERE", "WINDOW", "WITH",
+    ];
+
+    let upper = name.to_ascii_uppercase();
+    RESERVED.contains(&upper.as_str())
+}
@@ -0,0 +1,57 @@
+// Bridge from diesel `table!` schemas, see `hone_diesel!`
+pub use diesel;
+
+use diesel::sql_types::*;
+
+// Rust value of a diesel SQL type, used as the type of hone accessors
+pub trait DieselType {
+    type Value;
+    // Type of a column holding the value, `Option` when it is nullable
+    type Field;
+}
+
+macro_rules! diesel_type {
+    ($($t:ty => $v:ty),*) => {
+        $(
+            impl DieselType for $t {
+                type Value = $v;
+                type Field = $v;
+            }
+        )*
+    };
+}
+
+diesel_type!(
+    Bool => bool,
+    TinyInt => i8,
+    SmallInt => i16,
+    Integer => i32,
+    BigInt => i64,
+    Float => f32,
+    Double => f64,
+    Text => String,
+    Binary => Vec<u8>
+);
+
+// See `types::Nullable` for why the accessors drop the `Option`
+impl<T: DieselType + NotNull> DieselType for Nullable<T> {
+    type Value = T::Value;
+    type Field = Option<T::Value>;
+}
+
+// Rust names of the primary key columns of a `table!`. Its columns print
+// their names, so the key prints as `id` or `(user_id, library_id)`.
+pub fn key_columns<T: diesel::Table>(t: T) -> Vec<String>
+where
+    T::PrimaryKey: std::fmt::Debug,
+{
+    let key = format!("{:?}", t.primary_key());
+
+    key.trim_start_matches('(').trim_end_matches(')').split(", ").map(|c| c.to_string()).collect()
+}
+
+#[cfg(feature = "chrono")]
+diesel_type!(Date => chrono::NaiveDate, Time => chrono::NaiveTime, Timestamp => chrono::NaiveDateTime);
+
+#[cfg(feature = "rust_decimal")]
+diesel_type!(Numeric => rust_decimal::Decimal);
@@ -1,45 +1,95 @@
 use std::fmt;
 use std::marker::PhantomData;
 
+use crate::dialect::{dialect, quote_ident, unqualified_columns, Dialect};
 use crate::types::*;
 
 #[derive(Clone)]
 pub struct Entity<T>(PhantomData<T>);
 
+// Identifiers are kept as separate parts so each one can be quoted
 #[derive(Clone, Default)]
-pub struct Column(String, Option<String>);
+pub struct Column {
+    schema: Option<String>,
+    table: Option<String>,
+    name: String,
+    alias: Option<String>,
+}
 
 impl Column {
+    // Accepts `column`, `table.column` or `schema.table.column`
     pub fn new(name: &str) -> Column {
-        Column(name.to_string(), None)
+        let mut parts = name.rsplitn(3, '.');
+        let column = parts.next().unwrap_or_default();
+        let table = parts.next();
+        let schema = parts.next();
+
+        Column::from_parts(schema, table, column)
+    }
+
+    pub fn from_parts(schema: Option<&str>, table: Option<&str>, name: &str) -> Column {
+        Column {
+            schema: schema.map(|s| s.to_string()),
+            table: table.map(|s| s.to_string()),
+            name: name.to_string(),
+            alias: None,
+        }
     }
 
     pub fn name(&self) -> String {
-        match &self.1 {
+        match &self.alias {
             &Some(ref n) => n.clone(),
-            None => self.0.clone(),
+            None => join_parts(&[&self.schema, &self.table], &self.name, |s| s.to_string()),
         }
     }
 
     pub fn as_(&mut self, name: &str) -> Column {
-        Column(self.0.to_string(), Some(name.to_string()))
+        Column {
+            alias: Some(name.to_string()),
+            ..self.clone()
+        }
     }
+
+    pub fn qualified(&self) -> String {
+        if unqualified_columns() {
+            return quote_ident(&self.name);
+        }
+
+        join_parts(&[&self.schema, &self.table], &self.name, quote_ident)
+    }
+}
+
+// Last part of a rendered column, `User.email` -> `email`
+pub fn column_name(sql: &str) -> &str {
+    sql.rsplit('.').next().unwrap_or_default()
+}
+
+fn join_parts<F>(qualifiers: &[&Option<String>], name: &str, f: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    qualifiers
+        .iter()
+        .filter_map(|q| q.as_ref().map(|q| f(q)))
+        .chain(std::iter::once(f(name)))
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 impl fmt::Display for Column {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self.1 {
-            Some(ref s) => write!(f, "{}", s),
-            _ => write!(f, "{}", self.0),
+        match &self.alias {
+            Some(ref s) => write!(f, "{}", quote_ident(s)),
+            _ => write!(f, "{}", self.qualified()),
         }
     }
 }
 
-impl<A: ToString + ToLiteral> HasValue<A> for Column {
+impl<A: ToLiteral> HasValue<A> for Column {
     type Output = Column;
 
     fn to_sql(&self) -> String {
-        Self::Output::to_literal(&self.0)
+        self.qualified()
     }
 }
 
@@ -61,19 +111,49 @@ impl<A: ToString + ToLiteral> HasValue<A> for Star {
 }
 
 #[derive(Clone, Default)]
-pub struct Table(String, Option<String>);
+pub struct Table {
+    schema: Option<String>,
+    name: String,
+    alias: Option<String>,
+}
 
 impl Table {
+    // Accepts `table` or `schema.table`
     pub fn new(name: &str, alias: Option<String>) -> Table {
-        Table(name.to_string(), alias)
+        let mut parts = name.rsplitn(2, '.');
+        let table = parts.next().unwrap_or_default();
+        let schema = parts.next();
+
+        Table {
+            schema: schema.map(|s| s.to_string()),
+            name: table.to_string(),
+            alias,
+        }
     }
 
     pub fn name(&self) -> String {
-        self.0.clone()
+        join_parts(&[&self.schema], &self.name, |s| s.to_string())
+    }
+
+    pub fn alias(&self) -> Option<String> {
+        self.alias.clone()
     }
 
     pub fn as_(&mut self, name: &str) -> Table {
-        Table(self.0.to_string(), Some(name.to_string()))
+        Table {
+            alias: Some(name.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub fn qualified(&self) -> String {
+        join_parts(&[&self.schema], &self.name, quote_ident)
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.qualified())
     }
 }
 
@@ -82,3 +162,75 @@ pub trait HasEntityDef {
 
     fn columns() -> Vec<&'static str>;
 }
+
+// Column types and constraints, used to generate CREATE TABLE
+pub trait HasTableDef: HasEntityDef {
+    fn column_defs() -> Vec<ColumnDef>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ColumnDef {
+    pub name: String,
+    pub sql_type: fn(Dialect) -> &'static str,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+    pub unique: bool,
+    pub auto_increment: bool,
+}
+
+impl ColumnDef {
+    // Type and nullability follow the Rust type, `Option<T>` is nullable
+    pub fn new<T: SqlType>(name: &str) -> ColumnDef {
+        ColumnDef {
+            name: name.to_string(),
+            sql_type: T::column_type,
+            nullable: T::nullable(),
+            default: None,
+            primary_key: false,
+            unique: false,
+            auto_increment: false,
+        }
+    }
+
+    // The type of a struct field, e.g. `ColumnDef::of("name", |a: Account| a.name)`
+    pub fn of<M, T: SqlType>(name: &str, _field: fn(M) -> T) -> ColumnDef {
+        ColumnDef::new::<T>(name)
+    }
+
+    pub fn primary_key(self) -> ColumnDef {
+        ColumnDef { primary_key: true, ..self }
+    }
+
+    pub fn unique(self) -> ColumnDef {
+        ColumnDef { unique: true, ..self }
+    }
+
+    pub fn auto_increment(self) -> ColumnDef {
+        ColumnDef { auto_increment: true, ..self }
+    }
+
+    pub fn nullable(self) -> ColumnDef {
+        ColumnDef { nullable: true, ..self }
+    }
+
+    pub fn default<T: SqlLiteral>(self, v: T) -> ColumnDef {
+        ColumnDef {
+            default: Some(v.sql_literal()),
+            ..self
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        (self.sql_type)(dialect())
+    }
+}
+
+pub trait Insertable: HasEntityDef {
+    fn insert_values(&self) -> Vec<(&'static str, Option<String>)>;
+
+    // Columns filled in by the database, such as auto increment keys
+    fn auto_columns() -> Vec<&'static str> {
+        vec![]
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use std::rc::Rc;
 
+use crate::datetime::*;
+use crate::dialect::*;
 use crate::entity::*;
 use crate::query::*;
 use crate::types::*;
@@ -59,7 +61,7 @@ pub fn in_<A, B>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: impl HasValueList<A>
 where
     A: ToLiteral,
 {
-    let comp: Rc<dyn HasValue<A, Output = i32>> = parens_(rhs.to_string());
+    let comp: Rc<dyn HasValue<A, Output = i32>> = never_(rhs.to_string());
     if_not_empty_list(rhs, false, binop_(" IN ", &lhs, &comp))
 }
 
@@ -67,20 +69,20 @@ pub fn not_in_<A, B>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: impl HasValueLis
 where
     A: ToLiteral,
 {
-    let comp: Rc<dyn HasValue<A, Output = i32>> = parens_(rhs.to_string());
+    let comp: Rc<dyn HasValue<A, Output = i32>> = never_(rhs.to_string());
     if_not_empty_list(rhs, false, binop_(" NOT IN ", &lhs, &comp))
 }
 
 pub fn val_<'a, A>(typ: A) -> Rc<dyn 'a + HasValue<A, Output = A>>
 where
-    A: 'a + fmt::Display + ToLiteral,
+    A: 'a + SqlLiteral,
 {
-    never_(typ.to_string())
+    Rc::new(Literal(typ.sql_literal(), std::marker::PhantomData))
 }
 
 pub fn val_list_<'a, A, B>(vs: &[Rc<dyn 'a + HasValue<A, Output = B>>]) -> impl HasValueList<A>
 where
-    A: 'a + fmt::Display,
+    A: 'a,
     B: 'static + ToLiteral,
 {
     if vs.is_empty() {
@@ -93,6 +95,15 @@ where
     List::NonEmpty(Box::new(v)) as List<A, B>
 }
 
+// Row value built from a tuple of expressions, e.g. `(User.user_id, User.email)`
+pub fn row_<R>(r: R) -> Rc<dyn HasValue<R::Value, Output = CompositKey<R::Value>>>
+where
+    R: RowValue,
+    R::Value: 'static,
+{
+    Rc::new(CompositKey(r.row(), std::marker::PhantomData))
+}
+
 pub fn gt_<A, B, C>(lhs: &Rc<dyn HasValue<A, Output = B>>, rhs: &Rc<dyn HasValue<A, Output = C>>) -> Rc<dyn HasValue<bool, Output = bool>> {
     binop_(" > ", lhs, rhs)
 }
@@ -210,6 +221,78 @@ where
     parens_(select(q).to_sql())
 }
 
+pub fn in_sub_<A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, q: Query<Rc<dyn HasValue<A, Output = C>>>) -> Rc<dyn HasValue<bool, Output = bool>>
+where
+    A: fmt::Display,
+    C: 'static + ToLiteral,
+{
+    binop_(" IN ", &lhs, &sub_(q))
+}
+
+pub fn not_in_sub_<A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, q: Query<Rc<dyn HasValue<A, Output = C>>>) -> Rc<dyn HasValue<bool, Output = bool>>
+where
+    A: fmt::Display,
+    C: 'static + ToLiteral,
+{
+    binop_(" NOT IN ", &lhs, &sub_(q))
+}
+
+fn quantified_<A, B, C>(op: &str, quantifier: &str, lhs: Rc<dyn HasValue<A, Output = B>>, q: Query<Rc<dyn HasValue<A, Output = C>>>) -> Rc<dyn HasValue<bool, Output = bool>>
+where
+    A: 'static + fmt::Display,
+    C: 'static + ToLiteral,
+{
+    if dialect() != Dialect::Sqlite {
+        return parens_(format!("{} {} {} {}", lhs.to_sql(), op, quantifier, sub_(q).to_sql()));
+    }
+
+    // SQLite has no ANY / ALL, so the subquery is scanned as a derived table instead.
+    let name = "_v";
+    let value: Rc<dyn HasValue<A, Output = Column>> = Rc::new(q.value.as_(name));
+    let from = sub_(q.return_(value)).to_sql();
+    let comp = format!("({} {} {})", lhs.to_sql(), op, quote_ident(name));
+
+    match quantifier {
+        "ALL" => parens_(format!("NOT EXISTS (SELECT 1 FROM {} WHERE {} IS NOT TRUE)", from, comp)),
+        _ => parens_(format!("EXISTS (SELECT 1 FROM {} WHERE {})", from, comp)),
+    }
+}
+
+macro_rules! quantified {
+    ($($name:ident => ($op:expr, $quantifier:expr)),*) => {
+        $(
+            pub fn $name<A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, q: Query<Rc<dyn HasValue<A, Output = C>>>) -> Rc<dyn HasValue<bool, Output = bool>>
+            where
+                A: 'static + fmt::Display,
+                C: 'static + ToLiteral,
+            {
+                quantified_($op, $quantifier, lhs, q)
+            }
+        )*
+    };
+}
+
+quantified!(
+    eq_any_ => ("=", "ANY"),
+    not_eq_any_ => ("<>", "ANY"),
+    gt_any_ => (">", "ANY"),
+    gte_any_ => (">=", "ANY"),
+    lt_any_ => ("<", "ANY"),
+    lte_any_ => ("<=", "ANY"),
+    eq_some_ => ("=", "SOME"),
+    not_eq_some_ => ("<>", "SOME"),
+    gt_some_ => (">", "SOME"),
+    gte_some_ => (">=", "SOME"),
+    lt_some_ => ("<", "SOME"),
+    lte_some_ => ("<=", "SOME"),
+    eq_all_ => ("=", "ALL"),
+    not_eq_all_ => ("<>", "ALL"),
+    gt_all_ => (">", "ALL"),
+    gte_all_ => (">=", "ALL"),
+    lt_all_ => ("<", "ALL"),
+    lte_all_ => ("<=", "ALL")
+);
+
 fn unsafe_sql_function<'a, A, B, C>(name: &str, arg: A, parens: NeedParens) -> Rc<dyn 'a + HasValue<B, Output = C>>
 where
     A: UnsafeSqlFunctionArgument,
@@ -257,6 +340,20 @@ where
     Rc::new(SetValue(lhs, rhs))
 }
 
+// The row proposed for insertion in an ON CONFLICT ... DO UPDATE clause
+pub fn excluded_<A>(col: Rc<dyn HasValue<A, Output = Column>>) -> Rc<dyn HasValue<A, Output = Column>>
+where
+    A: 'static,
+{
+    let sql = col.to_sql();
+    let name = column_name(&sql);
+
+    match dialect() {
+        Dialect::Mysql => never_(format!("VALUES({})", name)),
+        _ => never_(format!("excluded.{}", name)),
+    }
+}
+
 pub fn sum_<'a, A>(a: A) -> Rc<dyn 'a + HasValue<u32, Output = Column>>
 where
     A: 'a + UnsafeSqlFunctionArgument,
@@ -285,6 +382,118 @@ where
     unsafe_sql_function("ROUND", a, NeedParens::Parens)
 }
 
+pub fn coalesce_<'a, A, N: Nullable<A>, B, C>(a: Rc<dyn HasValue<N, Output = B>>, default: Rc<dyn HasValue<A, Output = C>>) -> Rc<dyn 'a + HasValue<A, Output = Column>> {
+    never_(format!("COALESCE({}, {})", a.to_sql(), default.to_sql()))
+}
+
+pub fn nullif_<'a, A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: Rc<dyn HasValue<A, Output = C>>) -> Rc<dyn 'a + HasValue<Option<A>, Output = Column>> {
+    never_(format!("NULLIF({}, {})", lhs.to_sql(), rhs.to_sql()))
+}
+
+pub fn greatest_<'a, A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: Rc<dyn HasValue<A, Output = C>>) -> Rc<dyn 'a + HasValue<A, Output = Column>> {
+    let name = match dialect() {
+        Dialect::Sqlite => "MAX",
+        _ => "GREATEST",
+    };
+
+    never_(format!("{}({}, {})", name, lhs.to_sql(), rhs.to_sql()))
+}
+
+pub fn least_<'a, A, B, C>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: Rc<dyn HasValue<A, Output = C>>) -> Rc<dyn 'a + HasValue<A, Output = Column>> {
+    let name = match dialect() {
+        Dialect::Sqlite => "MIN",
+        _ => "LEAST",
+    };
+
+    never_(format!("{}({}, {})", name, lhs.to_sql(), rhs.to_sql()))
+}
+
+pub fn cast_<'a, T>(a: impl UnsafeSqlFunctionArgument) -> Rc<dyn 'a + HasValue<T, Output = Column>>
+where
+    T: SqlType,
+{
+    let args = UnsafeSqlFunctionArgument::to_arg_list(&a);
+    let results = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    never_(format!("CAST({} AS {})", results.join(","), T::type_name(dialect())))
+}
+
+// The current date, time or timestamp depending on `A`
+pub fn now_<'a, A: Temporal>() -> Rc<dyn 'a + HasValue<A, Output = Column>> {
+    match (dialect(), A::kind()) {
+        (Dialect::Sqlite, TemporalKind::Date) => never_("date('now')"),
+        (Dialect::Sqlite, TemporalKind::Time) => never_("time('now')"),
+        (Dialect::Sqlite, TemporalKind::DateTime) => never_("datetime('now')"),
+        (_, TemporalKind::Date) => never_("CURRENT_DATE"),
+        (_, TemporalKind::Time) => never_("CURRENT_TIME"),
+        (Dialect::Postgres, _) | (Dialect::Mysql, _) => never_("NOW()"),
+        (Dialect::Standard, _) => never_("CURRENT_TIMESTAMP"),
+    }
+}
+
+pub fn current_date_<'a, A: HasDate>() -> Rc<dyn 'a + HasValue<A, Output = Column>> {
+    now_::<A>()
+}
+
+pub fn date_trunc_<'a, A, B>(part: DatePart, a: Rc<dyn HasValue<A, Output = B>>) -> Rc<dyn 'a + HasValue<A, Output = Column>>
+where
+    A: Temporal,
+{
+    let d = dialect();
+    let s = match d {
+        Dialect::Sqlite => format!("strftime('{}', {})", part.truncate_format(d), a.to_sql()),
+        Dialect::Mysql => format!("CAST(DATE_FORMAT({}, '{}') AS DATETIME)", a.to_sql(), part.truncate_format(d)),
+        _ => format!("date_trunc('{}', {})", part.to_string().to_lowercase(), a.to_sql()),
+    };
+
+    never_(s)
+}
+
+pub fn extract_<'a, A, B>(part: DatePart, a: Rc<dyn HasValue<A, Output = B>>) -> Rc<dyn 'a + HasValue<i32, Output = Column>>
+where
+    A: Temporal,
+{
+    let s = match dialect() {
+        Dialect::Sqlite => format!("CAST(strftime('{}', {}) AS INTEGER)", part.sqlite_format(), a.to_sql()),
+        _ => format!("EXTRACT({} FROM {})", part, a.to_sql()),
+    };
+
+    never_(s)
+}
+
+pub fn date_add_<'a, A, B, C, D>(a: Rc<dyn HasValue<A, Output = B>>, interval: Rc<dyn HasValue<C, Output = D>>) -> Rc<dyn 'a + HasValue<A, Output = Column>>
+where
+    A: Temporal,
+    C: HasInterval,
+{
+    match dialect() {
+        Dialect::Sqlite => never_(format!("{}({}, {})", sqlite_date_function::<A>(), a.to_sql(), interval.to_sql())),
+        _ => parens_(a.to_sql() + " + " + &interval.to_sql()),
+    }
+}
+
+pub fn date_sub_<'a, A, B, C, D>(a: Rc<dyn HasValue<A, Output = B>>, interval: Rc<dyn HasValue<C, Output = D>>) -> Rc<dyn 'a + HasValue<A, Output = Column>>
+where
+    A: Temporal,
+    C: HasInterval,
+{
+    match dialect() {
+        Dialect::Sqlite => {
+            let negated = format!("printf('%f seconds', -CAST({} AS REAL))", interval.to_sql());
+            never_(format!("{}({}, {})", sqlite_date_function::<A>(), a.to_sql(), negated))
+        }
+        _ => parens_(a.to_sql() + " - " + &interval.to_sql()),
+    }
+}
+
+fn sqlite_date_function<A: Temporal>() -> &'static str {
+    match A::kind() {
+        TemporalKind::Date => "date",
+        TemporalKind::Time => "time",
+        TemporalKind::DateTime => "datetime",
+    }
+}
+
 pub fn like_<'a, A, B>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: Rc<dyn HasValue<String, Output = String>>) -> Rc<dyn 'a + HasValue<bool, Output = String>>
 where
     B: ToLiteral,
@@ -297,8 +506,45 @@ pub fn ilike_<'a, A, B>(lhs: Rc<dyn HasValue<A, Output = B>>, rhs: Rc<dyn HasVal
 where
     B: ToLiteral,
 {
-    let op: Rc<dyn HasValue<A, Output = B>> = never_(lhs.to_sql());
-    parens_(op.to_sql() + " ILIKE " + &rhs.to_sql())
+    parens_(ilike_sql(&lhs.to_sql(), &rhs.to_sql()))
+}
+
+pub fn like_escape_<'a, A, B>(
+    lhs: Rc<dyn HasValue<A, Output = B>>,
+    rhs: Rc<dyn HasValue<String, Output = String>>,
+    escape: char,
+) -> Rc<dyn 'a + HasValue<bool, Output = bool>> {
+    parens_(lhs.to_sql() + " LIKE " + &rhs.to_sql() + " ESCAPE " + &quote_string(&escape.to_string()))
+}
+
+pub fn ilike_escape_<'a, A, B>(
+    lhs: Rc<dyn HasValue<A, Output = B>>,
+    rhs: Rc<dyn HasValue<String, Output = String>>,
+    escape: char,
+) -> Rc<dyn 'a + HasValue<bool, Output = bool>> {
+    parens_(ilike_sql(&lhs.to_sql(), &rhs.to_sql()) + " ESCAPE " + &quote_string(&escape.to_string()))
+}
+
+// ILIKE only exists on PostgreSQL, elsewhere both sides are lowercased
+fn ilike_sql(lhs: &str, rhs: &str) -> String {
+    match dialect() {
+        Dialect::Postgres => format!("{} ILIKE {}", lhs, rhs),
+        _ => format!("LOWER({}) LIKE LOWER({})", lhs, rhs),
+    }
+}
+
+// Escapes the LIKE wildcards of `s` so that it only matches itself
+pub fn escape_like(s: &str, escape: char) -> String {
+    let mut result = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        if c == '%' || c == '_' || c == escape {
+            result.push(escape);
+        }
+        result.push(c);
+    }
+
+    result
 }
 
 pub fn don_<A, B>(a: Rc<dyn HasValue<A, Output = B>>) -> Box<dyn HasDistinct>
@@ -0,0 +1,408 @@
+// Formats rendered SQL for reading: one clause per line, subqueries and
+// CTE bodies indented, and the parentheses `binop_` puts around every
+// expression dropped where operator precedence makes them redundant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub multiline: bool,
+    pub indent: usize,
+    pub strip_parens: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            multiline: true,
+            indent: 2,
+            strip_parens: true,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn multiline(self, b: bool) -> FormatOptions {
+        FormatOptions { multiline: b, ..self }
+    }
+
+    pub fn indent(self, n: usize) -> FormatOptions {
+        FormatOptions { indent: n, ..self }
+    }
+
+    pub fn strip_parens(self, b: bool) -> FormatOptions {
+        FormatOptions { strip_parens: b, ..self }
+    }
+}
+
+pub fn format_sql(sql: &str, opts: &FormatOptions) -> String {
+    let mut tokens = tokenize(sql);
+
+    if opts.strip_parens {
+        tokens = strip_parens(tokens);
+    }
+
+    if opts.multiline {
+        layout(&tokens, opts.indent)
+    } else {
+        tokens.iter().enumerate().fold(String::new(), |mut s, (i, t)| {
+            if i > 0 && t.space {
+                s.push(' ');
+            }
+            s.push_str(&t.text);
+            s
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Word,
+    Literal,
+    Op,
+    Open,
+    Close,
+    Comma,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    // whether the token was preceded by whitespace
+    space: bool,
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars = sql.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut space = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                space = true;
+                i += 1;
+                continue;
+            }
+            '\'' | '"' | '`' => {
+                i += 1;
+                // a doubled quote is an escaped one
+                while i < chars.len() {
+                    if chars[i] == c {
+                        if chars.get(i + 1) == Some(&c) {
+                            i += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                if c == '\'' {
+                    Kind::Literal
+                } else {
+                    Kind::Word
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                Kind::Literal
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                    i += 1;
+                }
+                Kind::Word
+            }
+            '(' | ')' | ',' | '.' | ';' => {
+                i += 1;
+                match c {
+                    '(' => Kind::Open,
+                    ')' => Kind::Close,
+                    ',' => Kind::Comma,
+                    _ => Kind::Other,
+                }
+            }
+            _ => {
+                while i < chars.len() && "<>=!|:+-*/%~&^@#?".contains(chars[i]) {
+                    i += 1;
+                }
+                i = i.max(start + 1);
+                Kind::Op
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            text: chars[start..i].iter().collect(),
+            space,
+        });
+        space = false;
+    }
+
+    tokens
+}
+
+// Keywords, but not column names that happen to spell one, e.g. `t.type`
+fn keyword(tokens: &[Token], i: usize) -> Option<String> {
+    let t = tokens.get(i)?;
+    if t.kind != Kind::Word || (i > 0 && tokens[i - 1].text == ".") {
+        return None;
+    }
+    Some(t.text.to_uppercase())
+}
+
+const ATOM: u8 = 100;
+// Operators whose precedence differs between dialects (`||` binds tighter
+// than `*` on SQLite); only atoms lose their parentheses next to them.
+const OPAQUE: u8 = 10;
+
+// Binding strength of an operator inside an expression
+fn precedence(tokens: &[Token], i: usize) -> Option<u8> {
+    let t = &tokens[i];
+    match t.kind {
+        Kind::Op => Some(match t.text.as_str() {
+            "=" | "==" | "<>" | "!=" | "<" | "<=" | ">" | ">=" => 4,
+            "+" | "-" => 6,
+            "*" | "/" | "%" => 7,
+            "::" => 9,
+            _ => 5,
+        }),
+        Kind::Word => match keyword(tokens, i)?.as_str() {
+            "OR" => Some(1),
+            "AND" => Some(2),
+            "NOT" => Some(3),
+            "IS" | "LIKE" | "ILIKE" | "IN" | "BETWEEN" | "ESCAPE" | "GLOB" | "REGEXP" | "MATCH" => Some(4),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Lowest binding strength at the top level of tokens[lo..hi], or None if
+// the group is not a plain expression (a list, a subquery, ...).
+fn inner_precedence(tokens: &[Token], lo: usize, hi: usize) -> Option<u8> {
+    if lo == hi {
+        return None;
+    }
+
+    let mut depth = 0;
+    let mut cases = 0;
+    let mut lowest = ATOM;
+
+    for i in lo..hi {
+        match tokens[i].kind {
+            Kind::Open => depth += 1,
+            Kind::Close => depth -= 1,
+            Kind::Comma if depth == 0 => return None,
+            _ if depth > 0 => {}
+            _ => match keyword(tokens, i).as_deref() {
+                Some("SELECT") | Some("WITH") | Some("VALUES") => return None,
+                Some("CASE") => cases += 1,
+                Some("END") => cases -= 1,
+                _ if cases > 0 => {}
+                _ => lowest = precedence(tokens, i).map_or(lowest, |p| p.min(lowest)),
+            },
+        }
+    }
+
+    Some(lowest)
+}
+
+fn is_operand(t: &Token) -> bool {
+    matches!(t.kind, Kind::Word | Kind::Literal | Kind::Close)
+}
+
+// What a group sees to its left: Some(0) for the start of an expression,
+// the operator's precedence, or None when the parentheses belong to the
+// token before them (calls, IN lists, DISTINCT ON, ...).
+fn left_context(tokens: &[Token], i: usize) -> Option<u8> {
+    if i == 0 {
+        return Some(0);
+    }
+
+    let j = i - 1;
+    match tokens[j].kind {
+        Kind::Open | Kind::Comma => Some(0),
+        Kind::Other if tokens[j].text == ";" => Some(0),
+        Kind::Op => {
+            let unary = j == 0 || !is_operand(&tokens[j - 1]) || precedence(&tokens[..j], j - 1).is_some();
+            match precedence(tokens, j)? {
+                6 if unary => Some(OPAQUE),
+                4 | 6 | 7 => precedence(tokens, j),
+                _ => Some(OPAQUE),
+            }
+        }
+        Kind::Word => match keyword(tokens, j)?.as_str() {
+            "ON" if j > 0 && keyword(tokens, j - 1).as_deref() == Some("DISTINCT") => None,
+            "SELECT" | "WHERE" | "ON" | "HAVING" | "WHEN" | "THEN" | "ELSE" | "RETURNING" => Some(0),
+            "OR" | "AND" | "NOT" => precedence(tokens, j),
+            "IS" | "LIKE" | "ILIKE" | "BETWEEN" | "ESCAPE" => Some(4),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn right_context(tokens: &[Token], i: usize) -> Option<u8> {
+    let t = match tokens.get(i) {
+        Some(t) => t,
+        None => return Some(0),
+    };
+
+    match t.kind {
+        Kind::Close | Kind::Comma => Some(0),
+        Kind::Other if t.text == ";" => Some(0),
+        Kind::Op => match precedence(tokens, i)? {
+            p @ (4 | 6 | 7) => Some(p),
+            _ => Some(OPAQUE),
+        },
+        Kind::Word => match keyword(tokens, i)?.as_str() {
+            "FROM" | "WHERE" | "AS" | "ASC" | "DESC" | "THEN" | "WHEN" | "ELSE" | "END" | "GROUP" | "ORDER" | "HAVING" | "LIMIT" | "OFFSET"
+            | "UNION" | "INTERSECT" | "EXCEPT" | "INNER" | "LEFT" | "RIGHT" | "FULL" | "CROSS" | "JOIN" | "RETURNING" => Some(0),
+            "NOT" => Some(4),
+            _ => precedence(tokens, i),
+        },
+        _ => None,
+    }
+}
+
+fn redundant(inner: u8, left: Option<u8>, right: Option<u8>) -> bool {
+    // AND and OR are associative; the other operators only on their left operand
+    let left = left.is_some_and(|l| inner > l || (inner == l && (l == 1 || l == 2)));
+    let right = right.is_some_and(|r| inner > r || (inner == r && matches!(r, 1 | 2 | 6 | 7)));
+    left && right
+}
+
+fn strip_parens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut pairs = vec![0; tokens.len()];
+    let mut stack = vec![];
+    for (i, t) in tokens.iter().enumerate() {
+        match t.kind {
+            Kind::Open => stack.push(i),
+            Kind::Close => match stack.pop() {
+                Some(j) => {
+                    pairs[j] = i;
+                }
+                None => return tokens,
+            },
+            _ => {}
+        }
+    }
+    if !stack.is_empty() {
+        return tokens;
+    }
+
+    let mut dropped = vec![false; tokens.len()];
+    mark_redundant(&tokens, &pairs, 0, tokens.len(), Some(0), Some(0), &mut dropped);
+
+    let mut result: Vec<Token> = vec![];
+    let mut skipped = None;
+    for (t, dropped) in tokens.into_iter().zip(dropped) {
+        if dropped {
+            skipped = Some(skipped.unwrap_or(false) || t.space);
+            continue;
+        }
+
+        let mut t = t;
+        if let Some(space) = skipped.take() {
+            let joined = result.last().is_some_and(|p| matches!(p.kind, Kind::Word | Kind::Literal)) && matches!(t.kind, Kind::Word | Kind::Literal);
+            t.space = t.space || space || joined;
+        }
+        result.push(t);
+    }
+
+    result
+}
+
+// A dropped group's edges take over the context of the group itself
+fn mark_redundant(tokens: &[Token], pairs: &[usize], lo: usize, hi: usize, left: Option<u8>, right: Option<u8>, dropped: &mut [bool]) {
+    let mut i = lo;
+    while i < hi {
+        if tokens[i].kind != Kind::Open {
+            i += 1;
+            continue;
+        }
+
+        let j = pairs[i];
+        let l = if i == lo { left } else { left_context(tokens, i) };
+        let r = if j + 1 == hi { right } else { right_context(tokens, j + 1) };
+
+        match inner_precedence(tokens, i + 1, j) {
+            Some(p) if redundant(p, l, r) => {
+                dropped[i] = true;
+                dropped[j] = true;
+                mark_redundant(tokens, pairs, i + 1, j, l, r, dropped);
+            }
+            _ => mark_redundant(tokens, pairs, i + 1, j, Some(0), Some(0), dropped),
+        }
+
+        i = j + 1;
+    }
+}
+
+fn starts_clause(tokens: &[Token], i: usize) -> bool {
+    let word = match keyword(tokens, i) {
+        Some(w) => w,
+        None => return false,
+    };
+    let prev = if i > 0 { keyword(tokens, i - 1) } else { None };
+    let next = keyword(tokens, i + 1);
+
+    // LEFT(...), VALUES(...) and the like are function calls
+    if tokens.get(i + 1).is_some_and(|t| t.kind == Kind::Open && !t.space) {
+        return false;
+    }
+
+    match word.as_str() {
+        "SELECT" | "WHERE" | "GROUP" | "HAVING" | "ORDER" | "LIMIT" | "UNION" | "INTERSECT" | "EXCEPT" | "VALUES" | "SET" | "RETURNING"
+        | "INSERT" | "DELETE" | "WITH" | "INNER" | "LEFT" | "RIGHT" | "FULL" | "CROSS" | "WINDOW" => true,
+        "FROM" => prev.as_deref() != Some("DELETE"),
+        "UPDATE" => !matches!(prev.as_deref(), Some("DO") | Some("KEY")),
+        "JOIN" => !matches!(
+            prev.as_deref(),
+            Some("INNER") | Some("LEFT") | Some("RIGHT") | Some("FULL") | Some("CROSS") | Some("OUTER")
+        ),
+        "ON" => matches!(next.as_deref(), Some("CONFLICT") | Some("DUPLICATE")),
+        _ => false,
+    }
+}
+
+fn layout(tokens: &[Token], indent: usize) -> String {
+    let mut out = String::new();
+    // one entry per open parenthesis, true for a subquery
+    let mut scopes: Vec<bool> = vec![];
+    let mut statement_end = false;
+
+    for (i, t) in tokens.iter().enumerate() {
+        let mut newline = statement_end;
+        statement_end = false;
+
+        match t.kind {
+            Kind::Open => {}
+            Kind::Close => newline = scopes.pop().unwrap_or(false),
+            _ => newline = newline || (scopes.last().copied().unwrap_or(true) && starts_clause(tokens, i)),
+        }
+
+        if i > 0 && newline {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent * scopes.iter().filter(|s| **s).count()));
+        } else if i > 0 && t.space {
+            out.push(' ');
+        }
+        out.push_str(&t.text);
+
+        match t.kind {
+            Kind::Open => scopes.push(matches!(keyword(tokens, i + 1).as_deref(), Some("SELECT") | Some("WITH"))),
+            Kind::Other if t.text == ";" => statement_end = true,
+            _ => {}
+        }
+    }
+
+    out
+}
@@ -1,5 +1,12 @@
+pub mod datetime;
+pub mod dialect;
+#[cfg(feature = "diesel")]
+pub mod diesel_interop;
 pub mod entity;
 pub mod expression;
+pub mod format;
 pub mod macros;
+pub mod plan;
 pub mod query;
+pub mod schema;
 pub mod types;
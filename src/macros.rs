@@ -4,7 +4,7 @@ macro_rules! hone_model {
         impl $model {
             $(
                 pub fn $column(&self) -> Rc<HasValue<$type, Output=Column>> {
-                    Rc::new(Column::new(format!("{}.{}", stringify!($table), stringify!($column)).as_str()))
+                    Rc::new(Column::from_parts(None, Some(stringify!($table)), stringify!($column)))
                 }
             )*
         }
@@ -33,3 +33,140 @@ macro_rules! hone_entity {
         }
     };
 }
+
+#[macro_export]
+macro_rules! hone_insertable {
+    ($model:ident, auto($($auto:ident),*), $($col:ident),* ) => {
+        impl Insertable for $model {
+            fn insert_values(&self) -> Vec<(&'static str, Option<String>)> {
+                vec![$((stringify!($col), SqlLiteral::insert_value(&self.$col))),*]
+            }
+
+            fn auto_columns() -> Vec<&'static str> {
+                vec![$(stringify!($auto)),*]
+            }
+        }
+    };
+    ($model:ident, $($col:ident),* ) => {
+        hone_insertable!($model, auto(), $($col),*);
+    };
+}
+
+// Accessors, HasEntityDef, HasTableDef and HasQuery for a diesel `table!`,
+// which needs the "diesel" feature. Column names, types and the primary key
+// are taken from diesel, and listing other columns than the table has fails
+// to compile.
+//
+// hone_diesel!(Download, downloads, id, version)
+#[macro_export]
+macro_rules! hone_diesel {
+    ($model:ident, $table:ident, $($column:ident),+ ) => {
+        impl $model {
+            $(
+                pub fn $column(
+                    &self,
+                ) -> Rc<dyn HasValue<
+                    <<$table::$column as $crate::diesel_interop::diesel::Expression>::SqlType as $crate::diesel_interop::DieselType>::Value,
+                    Output = Column,
+                >> {
+                    let name = <$table::$column as $crate::diesel_interop::diesel::Column>::NAME;
+                    Rc::new(Column::from_parts(None, Some(stringify!($table)), name))
+                }
+            )*
+        }
+
+        impl HasEntityDef for $model {
+            fn table_name() -> Table {
+                Table::new(stringify!($table), None)
+            }
+
+            fn columns() -> Vec<&'static str> {
+                let _: <$table::table as $crate::diesel_interop::diesel::Table>::AllColumns = ($($table::$column,)+);
+
+                vec![$(<$table::$column as $crate::diesel_interop::diesel::Column>::NAME),+]
+            }
+        }
+
+        impl HasTableDef for $model {
+            fn column_defs() -> Vec<ColumnDef> {
+                let keys = $crate::diesel_interop::key_columns($table::table);
+
+                vec![$({
+                    let name = <$table::$column as $crate::diesel_interop::diesel::Column>::NAME;
+                    let def = ColumnDef::new::<
+                        <<$table::$column as $crate::diesel_interop::diesel::Expression>::SqlType as $crate::diesel_interop::DieselType>::Field,
+                    >(name);
+
+                    if keys.iter().any(|k| k == stringify!($column)) {
+                        def.primary_key()
+                    } else {
+                        def
+                    }
+                }),+]
+            }
+        }
+
+        impl HasQuery for $model {
+            type T = $model;
+        }
+    };
+}
+
+// Column types are those of the struct fields, `Option<T>` is nullable.
+//
+// hone_table!(User, user_id [primary_key, auto_increment], email [unique, default("")])
+#[macro_export]
+macro_rules! hone_table {
+    ($model:ident, $($column:ident $([$($option:ident $(($($arg:expr),*))?),*])?),+ ) => {
+        impl HasTableDef for $model {
+            fn column_defs() -> Vec<ColumnDef> {
+                vec![$(ColumnDef::of(stringify!($column), |m: $model| m.$column) $($(.$option($($($arg),*)?))*)?),+]
+            }
+        }
+    };
+}
+
+// Calls `$m!` for every tuple arity from 2 to 16. Each element is passed as
+// (type parameter, second type parameter, binding, index).
+macro_rules! for_each_tuple {
+    ($m:ident) => {
+        $m!((A, OA, a, 0), (B, OB, b, 1));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7));
+        $m!((A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8));
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10), (L, OL, l, 11)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10), (L, OL, l, 11), (M, OM, m, 12)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10), (L, OL, l, 11), (M, OM, m, 12), (N, ON, n, 13)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10), (L, OL, l, 11), (M, OM, m, 12), (N, ON, n, 13), (O, OO, o, 14)
+        );
+        $m!(
+            (A, OA, a, 0), (B, OB, b, 1), (C, OC, c, 2), (D, OD, d, 3), (E, OE, e, 4), (F, OF, f, 5), (G, OG, g, 6), (H, OH, h, 7), (I, OI, i, 8),
+            (J, OJ, j, 9), (K, OK, k, 10), (L, OL, l, 11), (M, OM, m, 12), (N, ON, n, 13), (O, OO, o, 14), (P, OP, p, 15)
+        );
+    };
+}
+
+pub(crate) use for_each_tuple;
@@ -0,0 +1,86 @@
+use std::fmt;
+
+// Output of SQLite's EXPLAIN QUERY PLAN as a tree, see `Explain::query_plan`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanNode {
+    pub id: i32,
+    pub detail: String,
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QueryPlan {
+    pub nodes: Vec<PlanNode>,
+}
+
+impl QueryPlan {
+    // Rows of (id, parent, detail), parents come before their children
+    pub fn from_rows(rows: Vec<(i32, i32, String)>) -> QueryPlan {
+        QueryPlan { nodes: children(&rows, 0) }
+    }
+
+    // Every node, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &PlanNode> {
+        let mut nodes = vec![];
+        let mut stack = self.nodes.iter().rev().collect::<Vec<_>>();
+
+        while let Some(n) = stack.pop() {
+            nodes.push(n);
+            stack.extend(n.children.iter().rev());
+        }
+
+        nodes.into_iter()
+    }
+
+    // e.g. `SEARCH User USING INDEX idx_email (email=?)`
+    pub fn uses_index(&self, name: &str) -> bool {
+        self.iter().any(|n| {
+            let words = n.detail.split_whitespace().collect::<Vec<_>>();
+            words.windows(2).any(|w| w[0] == "INDEX" && w[1] == name)
+        })
+    }
+
+    // A full scan of the table, `SCAN User` (`SCAN TABLE User` before SQLite 3.36)
+    pub fn scans(&self, table: &str) -> bool {
+        self.iter().any(|n| {
+            let mut words = n.detail.split_whitespace().peekable();
+
+            if words.next() != Some("SCAN") {
+                return false;
+            }
+            words.next_if_eq(&"TABLE");
+
+            words.next() == Some(table) && !n.detail.contains(" INDEX ")
+        })
+    }
+}
+
+fn children(rows: &[(i32, i32, String)], parent: i32) -> Vec<PlanNode> {
+    rows.iter()
+        .filter(|(id, p, _)| *p == parent && *id != parent)
+        .map(|(id, _, detail)| PlanNode {
+            id: *id,
+            detail: detail.clone(),
+            children: children(rows, *id),
+        })
+        .collect()
+}
+
+// Same layout as the sqlite3 shell
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_nodes(f: &mut fmt::Formatter, nodes: &[PlanNode], prefix: &str) -> fmt::Result {
+            for (i, n) in nodes.iter().enumerate() {
+                let last = i + 1 == nodes.len();
+
+                writeln!(f, "{}{}{}", prefix, if last { "`--" } else { "|--" }, n.detail)?;
+                write_nodes(f, &n.children, &format!("{}{}", prefix, if last { "   " } else { "|  " }))?;
+            }
+
+            Ok(())
+        }
+
+        writeln!(f, "QUERY PLAN")?;
+        write_nodes(f, &self.nodes, "")
+    }
+}
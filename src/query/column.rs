@@ -1,4 +1,6 @@
+use crate::dialect::quote_ident;
 use crate::entity::HasEntityDef;
+use crate::macros::for_each_tuple;
 use crate::types::*;
 use std::rc::Rc;
 
@@ -32,7 +34,7 @@ where
     A: HasEntityDef,
 {
     fn cols(&self) -> String {
-        A::columns().join(", ")
+        A::columns().iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", ")
     }
 
     fn col_count() -> usize {
@@ -40,38 +42,18 @@ where
     }
 }
 
-impl<A, B> Column for (A, B)
-where
-    A: Column,
-    B: Column,
-{
-    fn cols(&self) -> String {
-        let ca = self.0.cols();
-        let cb = self.1.cols();
-
-        ca + ", " + &cb
-    }
-
-    fn col_count() -> usize {
-        A::col_count() + B::col_count()
-    }
+macro_rules! column_tuple {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t: Column),+> Column for ($($t,)+) {
+            fn cols(&self) -> String {
+                [$(self.$i.cols()),+].join(", ")
+            }
+
+            fn col_count() -> usize {
+                0 $(+ $t::col_count())+
+            }
+        }
+    };
 }
 
-impl<A, B, C> Column for (A, B, C)
-where
-    A: Column,
-    B: Column,
-    C: Column,
-{
-    fn cols(&self) -> String {
-        let ca = self.0.cols();
-        let cb = self.1.cols();
-        let cc = self.2.cols();
-
-        ca + ", " + &cb + ", " + &cc
-    }
-
-    fn col_count() -> usize {
-        A::col_count() + B::col_count() + C::col_count()
-    }
-}
+for_each_tuple!(column_tuple);
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::dialect::*;
+use crate::entity::{HasEntityDef, HasTableDef};
+use crate::query::*;
+use crate::schema::{ColumnSchema, IndexSchema, TableSchema};
+
+// Expressions in an index or CHECK need their own parentheses
+fn parenthesized(sql: &str) -> String {
+    let mut depth = 0;
+
+    for (i, c) in sql.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0 {
+            if i + 1 == sql.len() && i > 0 {
+                return sql.to_string();
+            }
+            break;
+        }
+    }
+
+    format!("({})", sql)
+}
+
+fn index_column(sql: &str) -> String {
+    let (expr, order) = match sql.strip_suffix(" DESC") {
+        Some(e) => (e, " DESC"),
+        None => (sql.strip_suffix(" ASC").unwrap_or(sql), ""),
+    };
+
+    if expr.chars().all(|c| c.is_alphanumeric() || "_\"`[]".contains(c)) {
+        format!("{}{}", expr, order)
+    } else {
+        format!("{}{}", parenthesized(expr), order)
+    }
+}
+
+impl<A> CreateTable<A> {
+    pub fn if_not_exists_(self) -> CreateTable<A> {
+        CreateTable(true, self.1, self.2)
+    }
+
+    pub fn constraint_<C: HasConstraint + 'static>(self, c: C) -> CreateTable<A> {
+        let mut constraints = self.1;
+        constraints.push(Rc::new(c));
+
+        CreateTable(self.0, constraints, self.2)
+    }
+}
+
+impl<A: HasTableDef> CreateTable<A> {
+    pub fn schema(&self) -> TableSchema {
+        TableSchema {
+            name: A::table_name().name(),
+            columns: A::column_defs().iter().map(ColumnSchema::new).collect(),
+            constraints: self.1.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+impl<A: HasTableDef> ToSql for CreateTable<A> {
+    fn to_sql(&self) -> String {
+        self.schema().create_sql(self.0)
+    }
+}
+
+impl<A> DropTable<A> {
+    pub fn if_exists_(self) -> DropTable<A> {
+        DropTable(true, self.1, self.2)
+    }
+
+    pub fn cascade_(self) -> DropTable<A> {
+        DropTable(self.0, Some(DropBehavior::Cascade), self.2)
+    }
+
+    pub fn restrict_(self) -> DropTable<A> {
+        DropTable(self.0, Some(DropBehavior::Restrict), self.2)
+    }
+}
+
+impl<A: HasEntityDef> ToSql for DropTable<A> {
+    fn to_sql(&self) -> String {
+        let mut sql = String::from("DROP TABLE ");
+
+        if self.0 {
+            sql += "IF EXISTS ";
+        }
+
+        sql += &A::table_name().qualified();
+
+        match self.1 {
+            // SQLite has no CASCADE / RESTRICT
+            Some(_) if dialect() == Dialect::Sqlite => sql,
+            Some(a) => sql + " " + &a.to_string(),
+            None => sql,
+        }
+    }
+}
+
+impl<A> CreateIndex<A> {
+    pub fn unique_(self) -> CreateIndex<A> {
+        CreateIndex { unique: true, ..self }
+    }
+
+    // Ignored on MySQL, which has no IF NOT EXISTS for indexes
+    pub fn if_not_exists_(self) -> CreateIndex<A> {
+        CreateIndex { if_not_exists: true, ..self }
+    }
+}
+
+impl<A: HasEntityDef + Default> CreateIndex<A> {
+    // Partial index, MySQL has none
+    pub fn where_(self, f: impl Fn(A) -> Rc<dyn HasValue<bool, Output = bool>>) -> Result<CreateIndex<A>, ()> {
+        if dialect() == Dialect::Mysql {
+            return Err(());
+        }
+
+        let cond = with_unqualified_columns(|| f(A::default()).to_sql());
+
+        Ok(CreateIndex {
+            where_clause: Some(cond),
+            ..self
+        })
+    }
+}
+
+impl<A: HasEntityDef> CreateIndex<A> {
+    pub fn schema(&self) -> IndexSchema {
+        IndexSchema {
+            name: self.name.clone(),
+            table: A::table_name().name(),
+            sql: self.to_sql(),
+        }
+    }
+}
+
+impl<A: HasEntityDef> ToSql for CreateIndex<A> {
+    fn to_sql(&self) -> String {
+        let table = A::table_name();
+        let columns = self.columns.iter().map(|c| index_column(c)).collect::<Vec<_>>();
+
+        let mut sql = String::from("CREATE ");
+
+        if self.unique {
+            sql += "UNIQUE ";
+        }
+
+        sql += "INDEX ";
+
+        if self.if_not_exists && dialect() != Dialect::Mysql {
+            sql += "IF NOT EXISTS ";
+        }
+
+        sql = format!("{}{} ON {} ({})", sql, quote_ident(&self.name), table.qualified(), columns.join(", "));
+
+        if let Some(cond) = &self.where_clause {
+            sql = sql + " WHERE " + cond;
+        }
+
+        sql
+    }
+}
+
+impl<A> DropIndex<A> {
+    pub fn if_exists_(self) -> DropIndex<A> {
+        DropIndex(self.0, true, self.2)
+    }
+}
+
+impl<A: HasEntityDef> ToSql for DropIndex<A> {
+    fn to_sql(&self) -> String {
+        match dialect() {
+            // MySQL indexes belong to their table
+            Dialect::Mysql => format!("DROP INDEX {} ON {}", quote_ident(&self.0), A::table_name().qualified()),
+            _ if self.1 => format!("DROP INDEX IF EXISTS {}", quote_ident(&self.0)),
+            _ => format!("DROP INDEX {}", quote_ident(&self.0)),
+        }
+    }
+}
+
+fn constraint_name(name: &Option<String>) -> String {
+    match name {
+        Some(n) => format!("CONSTRAINT {} ", quote_ident(n)),
+        None => String::new(),
+    }
+}
+
+impl ForeignKey {
+    pub fn name_(self, name: &str) -> ForeignKey {
+        ForeignKey {
+            name: Some(name.to_string()),
+            ..self
+        }
+    }
+
+    pub fn on_delete_(self, a: ReferentialAction) -> ForeignKey {
+        ForeignKey { on_delete: Some(a), ..self }
+    }
+
+    pub fn on_update_(self, a: ReferentialAction) -> ForeignKey {
+        ForeignKey { on_update: Some(a), ..self }
+    }
+}
+
+impl HasConstraint for ForeignKey {}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}FOREIGN KEY ({}) REFERENCES {} ({})",
+            constraint_name(&self.name),
+            self.columns.join(", "),
+            self.table,
+            self.references.join(", ")
+        )?;
+
+        if let Some(a) = self.on_delete {
+            write!(f, " ON DELETE {}", a)?;
+        }
+
+        if let Some(a) = self.on_update {
+            write!(f, " ON UPDATE {}", a)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Check {
+    pub fn name_(self, name: &str) -> Check {
+        Check(Some(name.to_string()), self.1)
+    }
+}
+
+impl HasConstraint for Check {}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}CHECK {}", constraint_name(&self.0), parenthesized(&self.1))
+    }
+}
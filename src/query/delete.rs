@@ -1,3 +1,5 @@
+use crate::dialect::*;
+use crate::entity::HasEntityDef;
 use crate::query::*;
 
 impl<A> Delete<A> {}
@@ -5,7 +7,7 @@ impl<A> Delete<A> {}
 impl<A: Column> ToSql for Delete<A> {
     fn to_sql(&self) -> String {
         let mut sql = String::from("DELETE");
-        let state = self.0.state.borrow();
+        let state = &self.0.state;
 
         if let Ok(a) = self.make_from(&state.from_clause) {
             sql = sql + " FROM " + &a;
@@ -19,22 +21,150 @@ impl<A: Column> ToSql for Delete<A> {
         if let Ok(a) = self.make_limit(&state.limit_clause) {
             sql = sql + " " + &a;
         }
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
+        }
 
         sql
     }
 }
 
-impl<A> Truncate<A> {}
+impl<T: HasEntityDef, A> DeleteUsing<T, A> {
+    // The target table, the other tables, and every ON and WHERE condition
+    fn make_parts(&self, state: &QueryState) -> (String, Vec<String>, Vec<String>) {
+        let target = T::table_name().qualified();
+        // checked by `delete_using`
+        let (mut tables, mut conds) = flatten_joins(&state.from_clause).unwrap_or_default();
+
+        if let Some(i) = tables.iter().position(|t| *t == target) {
+            tables.remove(i);
+        }
+
+        if let Ok(a) = self.make_where(&state.where_clause) {
+            conds.push(a);
+        }
+
+        (target, tables, conds)
+    }
+}
 
-impl<A: Column> ToSql for Truncate<A> {
+impl<T: HasEntityDef, A> ToSql for DeleteUsing<T, A> {
     fn to_sql(&self) -> String {
-        let mut sql = String::from("TRUNCATE TABLE ");
-        let state = self.0.state.borrow();
+        let state = &self.0.state;
 
-        if let Ok(a) = self.make_from(&state.from_clause) {
-            sql = sql + &a;
+        if dialect() == Dialect::Mysql {
+            let mut sql = String::from("DELETE ") + &T::table_name().qualified();
+
+            if let Ok(a) = self.make_from(&state.from_clause) {
+                sql = sql + " FROM " + &a;
+            }
+            if let Ok(a) = self.make_where(&state.where_clause) {
+                sql = sql + " WHERE " + &a;
+            }
+
+            return sql;
+        }
+
+        let (target, tables, conds) = self.make_parts(state);
+        let mut sql = String::from("DELETE FROM ") + &target;
+
+        match dialect() {
+            Dialect::Postgres => {
+                if !tables.is_empty() {
+                    sql = sql + " USING " + &tables.join(", ");
+                }
+                if !conds.is_empty() {
+                    sql = sql + " WHERE " + &conds.join(" AND ");
+                }
+            }
+            // No multi-table DELETE, so the other tables move into a correlated subquery
+            _ => {
+                if tables.is_empty() {
+                    if !conds.is_empty() {
+                        sql = sql + " WHERE " + &conds.join(" AND ");
+                    }
+                } else {
+                    sql = sql + " WHERE EXISTS (SELECT 1 FROM " + &tables.join(", ");
+                    if !conds.is_empty() {
+                        sql = sql + " WHERE " + &conds.join(" AND ");
+                    }
+                    sql += ")";
+                }
+            }
+        }
+
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
         }
 
         sql
     }
 }
+
+impl<A> Truncate<A> {
+    pub fn restart_identity_(self) -> Truncate<A> {
+        Truncate(self.0, Some(Identity::Restart), self.2)
+    }
+
+    pub fn continue_identity_(self) -> Truncate<A> {
+        Truncate(self.0, Some(Identity::Continue), self.2)
+    }
+
+    // MySQL and SQLite cannot truncate the tables referencing these
+    pub fn cascade_(self) -> Result<Truncate<A>, ()> {
+        if matches!(dialect(), Dialect::Mysql | Dialect::Sqlite) {
+            return Err(());
+        }
+
+        Ok(Truncate(self.0, self.1, Some(DropBehavior::Cascade)))
+    }
+
+    // Dropped on MySQL and SQLite, which always restrict
+    pub fn restrict_(self) -> Truncate<A> {
+        Truncate(self.0, self.1, Some(DropBehavior::Restrict))
+    }
+
+    // Checked by `truncate`
+    fn tables(&self) -> Vec<String> {
+        from_tables(&self.0.state.from_clause).unwrap_or_default()
+    }
+
+    // One statement on PostgreSQL, one per table on MySQL and SQLite
+    pub fn statements(&self) -> Vec<String> {
+        let tables = self.tables();
+
+        match dialect() {
+            // MySQL truncates one table per statement and always resets AUTO_INCREMENT.
+            Dialect::Mysql => tables.iter().map(|t| format!("TRUNCATE TABLE {}", t)).collect(),
+            // SQLite has no TRUNCATE. sqlite_sequence only exists once a table
+            // uses AUTOINCREMENT, so the counters are reset on request only.
+            Dialect::Sqlite => {
+                let mut stmts = tables.iter().map(|t| format!("DELETE FROM {}", t)).collect::<Vec<_>>();
+
+                if self.1 == Some(Identity::Restart) {
+                    let names = tables
+                        .iter()
+                        .filter_map(|t| unquote_ident(t).pop())
+                        .map(|t| quote_string(&t))
+                        .collect::<Vec<_>>();
+                    stmts.push(format!("DELETE FROM sqlite_sequence WHERE name IN ({})", names.join(", ")));
+                }
+
+                stmts
+            }
+            _ => {
+                let mut sql = String::from("TRUNCATE TABLE ") + &tables.join(", ");
+
+                if let Some(a) = self.1 {
+                    sql = sql + " " + &a.to_string();
+                }
+
+                if let Some(a) = self.2 {
+                    sql = sql + " " + &a.to_string();
+                }
+
+                vec![sql]
+            }
+        }
+    }
+}
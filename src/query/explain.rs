@@ -0,0 +1,87 @@
+use crate::dialect::*;
+use crate::query::*;
+
+#[cfg(feature = "sqlite")]
+use crate::plan::QueryPlan;
+
+impl<S: ToSql> Explain<S> {
+    // MySQL has no JSON output for ANALYZE
+    pub fn analyze_(self) -> Result<Explain<S>, ()> {
+        if dialect() == Dialect::Mysql && self.3 {
+            return Err(());
+        }
+
+        Ok(Explain(self.0, true, self.2, self.3, self.4))
+    }
+
+    // Ignored on MySQL
+    pub fn verbose_(self) -> Explain<S> {
+        Explain(self.0, self.1, true, self.3, self.4)
+    }
+
+    pub fn format_json_(self) -> Result<Explain<S>, ()> {
+        if dialect() == Dialect::Mysql && self.1 {
+            return Err(());
+        }
+
+        Ok(Explain(self.0, self.1, self.2, true, self.4))
+    }
+
+    // Runs EXPLAIN QUERY PLAN. Expressions are rendered as they are built, so
+    // the statement has to be built under SQLite, otherwise this fails.
+    #[cfg(feature = "sqlite")]
+    pub fn query_plan(&self, conn: &diesel::SqliteConnection) -> diesel::QueryResult<QueryPlan> {
+        use diesel::dsl::sql;
+        use diesel::prelude::*;
+        use diesel::result::Error;
+        use diesel::sql_types::{Integer, Text};
+
+        if self.4 != Dialect::Sqlite {
+            return Err(Error::QueryBuilderError(format!("statement built for {:?}, not SQLite", self.4).into()));
+        }
+
+        let query = with_dialect(Dialect::Sqlite, || self.to_sql());
+        let rows = sql::<(Integer, Integer, Integer, Text)>(&query).load::<(i32, i32, i32, String)>(conn)?;
+
+        Ok(QueryPlan::from_rows(
+            rows.into_iter().map(|(id, parent, _, detail)| (id, parent, detail)).collect(),
+        ))
+    }
+}
+
+impl<S: ToSql> ToSql for Explain<S> {
+    fn to_sql(&self) -> String {
+        let stmt = self.0.to_sql();
+
+        match dialect() {
+            // SQLite's plain EXPLAIN lists VM opcodes and takes no options
+            Dialect::Sqlite => format!("EXPLAIN QUERY PLAN {}", stmt),
+            Dialect::Mysql => {
+                let mut sql = String::from("EXPLAIN ");
+
+                if self.1 {
+                    sql += "ANALYZE ";
+                }
+
+                if self.3 {
+                    sql += "FORMAT=JSON ";
+                }
+
+                sql + &stmt
+            }
+            _ => {
+                let opts = [(self.1, "ANALYZE"), (self.2, "VERBOSE"), (self.3, "FORMAT JSON")]
+                    .iter()
+                    .filter(|(on, _)| *on)
+                    .map(|(_, o)| *o)
+                    .collect::<Vec<_>>();
+
+                if opts.is_empty() {
+                    format!("EXPLAIN {}", stmt)
+                } else {
+                    format!("EXPLAIN ({}) {}", opts.join(", "), stmt)
+                }
+            }
+        }
+    }
+}
@@ -1,102 +1,118 @@
 use std::borrow::Borrow;
-use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::dialect::*;
 use crate::entity::Column as CL;
 use crate::entity::*;
+use crate::macros::for_each_tuple;
 use crate::query::*;
 use crate::types::Values;
 
 impl<A> Query<A> {
     pub fn new(e: A) -> Self {
         Query {
-            state: Rc::new(RefCell::new(QueryState::default())),
+            state: Rc::new(QueryState::default()),
             value: e,
         }
     }
 
+    // Copy-on-write, a query cloned before a change keeps its own state
+    pub(crate) fn state_mut(&mut self) -> &mut QueryState {
+        Rc::make_mut(&mut self.state)
+    }
+
+    pub fn scope_<S: HasScope<A>>(self, s: S) -> Query<A> {
+        s.apply(self)
+    }
+
+    // The clauses of `other` are added to this query, see `impl Add for QueryState`
+    pub fn merge_<B>(mut self, other: &Query<B>) -> Query<A> {
+        self.state = Rc::new((*self.state).clone() + (*other.state).clone());
+        self
+    }
+
     pub fn return_<B>(self, ret: B) -> Query<B> {
         let mut q = Query::new(ret);
         q.state = self.state;
         q
     }
 
-    pub fn on_(self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
-        self.state.borrow_mut().from_clause.push(FromClause::OnClause(b));
+    pub fn on_(mut self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
+        self.state_mut().from_clause.push(FromClause::OnClause(b));
         self
     }
 
-    pub fn where_(self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
+    pub fn where_(mut self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
         let w = WhereClause::Where(b);
-        let mut s = self.state.borrow_mut().where_clause.add(w);
-        std::mem::swap(&mut s, &mut self.state.borrow_mut().where_clause);
+        let mut s = self.state_mut().where_clause.add(w);
+        std::mem::swap(&mut s, &mut self.state_mut().where_clause);
         self
     }
 
-    pub fn order_(self, b: Vec<Rc<dyn HasOrder>>) -> Query<A> {
-        self.state.borrow_mut().order_clause = b;
+    pub fn order_(mut self, b: Vec<Rc<dyn HasOrder>>) -> Query<A> {
+        self.state_mut().order_clause = b;
         self
     }
 
-    pub fn group_by_<T, DB: ToLiteral>(self, b: Rc<dyn HasValue<T, Output = DB>>) -> Query<A>
+    pub fn group_by_<T, DB: ToLiteral>(mut self, b: Rc<dyn HasValue<T, Output = DB>>) -> Query<A>
     where
         T: 'static,
         DB: 'static,
     {
         let v = GroupBy(b);
-        self.state.borrow_mut().groupby_clause.push(Box::new(v));
+        self.state_mut().groupby_clause.push(Rc::new(v));
         self
     }
 
-    pub fn having_(self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
+    pub fn having_(mut self, b: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
         let w = WhereClause::Where(b);
-        let n = self.state.borrow_mut().having_clause.clone();
+        let n = self.state_mut().having_clause.clone();
 
         {
-            (*self.state.borrow_mut()).having_clause = n.add(w);
+            (*self.state_mut()).having_clause = n.add(w);
         }
 
         self
     }
 
-    pub fn value_<T, DB: ToLiteral>(self, a: Rc<dyn HasValue<T, Output = CL>>, b: Rc<dyn HasValue<T, Output = DB>>) -> Query<A>
+    pub fn value_<T, DB: ToLiteral>(mut self, a: Rc<dyn HasValue<T, Output = CL>>, b: Rc<dyn HasValue<T, Output = DB>>) -> Query<A>
     where
         T: 'static,
         DB: 'static,
     {
-        let v = Box::new(SetValue(a, b));
-        self.state.borrow_mut().set_clause.push(v);
+        let v = Rc::new(SetValue(a, b));
+        self.state_mut().set_clause.push(v);
         self
     }
 
-    pub fn values_<T, S>(self, a: T, b: Vec<S>) -> Query<A>
+    pub fn values_<T, S>(mut self, a: T, b: Vec<S>) -> Query<A>
     where
         T: ToValues + 'static,
         S: ToValues + 'static,
     {
-        self.state.borrow_mut().values_clause = Some(Box::new(Values(a, b)));
+        self.state_mut().values_clause = Some(Rc::new(Values(a, b)));
         self
     }
 
-    pub fn limit_(self, a: u32) -> Query<A> {
-        let s = self.state.borrow_mut().limit_clause.clone();
+    pub fn limit_(mut self, a: u32) -> Query<A> {
+        let s = self.state_mut().limit_clause.clone();
         {
-            self.state.borrow_mut().limit_clause = s + LimitClause::Limit(Some(a), None);
+            self.state_mut().limit_clause = s + LimitClause::Limit(Some(a), None);
         }
         self
     }
 
-    pub fn offset_(self, a: u32) -> Query<A> {
-        let s = self.state.borrow_mut().limit_clause.clone();
+    pub fn offset_(mut self, a: u32) -> Query<A> {
+        let s = self.state_mut().limit_clause.clone();
         {
-            self.state.borrow_mut().limit_clause = s + LimitClause::Limit(None, Some(a));
+            self.state_mut().limit_clause = s + LimitClause::Limit(None, Some(a));
         }
         self
     }
 
-    pub fn distinct_on_(self, mut a: Vec<Box<dyn HasDistinct>>) -> Query<A> {
+    pub fn distinct_on_(mut self, mut a: Vec<Box<dyn HasDistinct>>) -> Query<A> {
         {
-            let s = &mut *self.state.borrow_mut();
+            let s = &mut *self.state_mut();
 
             match &mut s.distinct_clause {
                 Distinct::On(ref mut v) => {
@@ -111,32 +127,68 @@ impl<A> Query<A> {
         self
     }
 
-    pub fn dup_key_<S, T>(self, column: Rc<dyn HasValue<S, Output = CL>>, value: Rc<dyn HasValue<S, Output = T>>) -> Query<A>
+    pub fn dup_key_<S, T>(mut self, column: Rc<dyn HasValue<S, Output = CL>>, value: Rc<dyn HasValue<S, Output = T>>) -> Query<A>
     where
         S: 'static,
         T: 'static,
     {
         {
             let a = DuplicateKey(column, value);
-            self.state.borrow_mut().duplicate_clause.push(Box::new(a));
+            self.state_mut().duplicate_clause.push(Rc::new(a));
         }
         self
     }
 
+    // MySQL has no RETURNING
+    pub fn returning_<B: super::column::Column>(mut self, b: B) -> Result<Query<A>, ()> {
+        if dialect() == Dialect::Mysql {
+            return Err(());
+        }
+
+        self.state_mut().returning_clause = Some(b.cols());
+        Ok(self)
+    }
+
+    pub fn on_conflict_<B: super::column::Column>(self, target: B) -> OnConflict<A> {
+        OnConflict(self, ConflictTarget::Columns(target.cols()))
+    }
+
+    pub fn on_conflict_constraint_(self, name: &str) -> OnConflict<A> {
+        OnConflict(self, ConflictTarget::Constraint(name.to_string()))
+    }
+
     fn from_start() -> FromPreprocess<A>
     where
         A: Default + HasEntityDef,
     {
-        let from_ = FromClause::Start(A::table_name().name());
+        let from_ = FromClause::Start(A::table_name().qualified());
         FromPreprocess(A::default(), from_)
     }
 
     fn from_finish(q: &mut Query<A>, exp: FromPreprocess<A>) -> Result<A, ()> {
-        q.state.borrow_mut().from_clause.push(exp.1);
+        q.state_mut().from_clause.push(exp.1);
         Ok(exp.0)
     }
 }
 
+impl<A> Clone for Scope<A> {
+    fn clone(&self) -> Scope<A> {
+        Scope(self.0.clone())
+    }
+}
+
+impl<A> HasScope<A> for Scope<A> {
+    fn apply(&self, q: Query<A>) -> Query<A> {
+        (self.0)(q)
+    }
+}
+
+impl<A: 'static> Scope<A> {
+    pub fn and_<S: 'static + HasScope<A>>(self, s: S) -> Scope<A> {
+        Scope(Rc::new(move |q| s.apply(self.apply(q))))
+    }
+}
+
 trait IsJoin<A, B> {
     type Kind;
 
@@ -266,46 +318,52 @@ where
     }
 }
 
-impl<A, B> FromQuery for Query<(A, B)>
-where
-    A: Default + FromProcess<Item = A> + HasQuery<T = A>,
-    B: Default + FromProcess<Item = B> + HasQuery<T = B>,
-{
-    type Kind = (A, B);
+// Implicit FROM over several entities, each of which may be a join tree.
+// The FROM items are listed one after another and the WHERE clauses are ANDed.
+fn merge_states(states: &[&Rc<QueryState>]) -> QueryState {
+    let mut s = QueryState::default();
 
-    fn from_() -> Result<Query<Self::Kind>, ()> {
-        let a = Query::<A>::from_()?;
-        let b = Query::<B>::from_()?;
-        let qs = Query::new((a.value, b.value));
+    for state in states {
+        s.from_clause.extend(state.from_clause.iter().cloned());
+        s.where_clause = s.where_clause.add(state.where_clause.clone());
+    }
 
-        {
-            let mut sa = a.state.borrow_mut();
-            let mut sb = b.state.borrow_mut();
-            sa.from_clause.append(&mut sb.from_clause);
+    s
+}
 
-            let mut s = QueryState {
-                where_clause: sa.where_clause.add(sb.where_clause.clone()),
-                ..Default::default()
-            };
+macro_rules! from_query_tuple {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t),+> FromQuery for Query<($($t,)+)>
+        where
+            $($t: Default + FromProcess<Item = $t> + HasQuery<T = $t>),+
+        {
+            type Kind = ($($t,)+);
 
-            std::mem::replace(&mut s.from_clause, (* sa.from_clause).to_vec());
+            fn from_() -> Result<Query<Self::Kind>, ()> {
+                $(let $v = Query::<$t>::from_()?;)+
+                let s = merge_states(&[$(&$v.state),+]);
 
-            qs.state.replace(s);
-        }
+                let mut qs = Query::new(($($v.value,)+));
+                qs.state = Rc::new(s);
 
-        Ok(qs)
-    }
+                Ok(qs)
+            }
 
-    fn from_by<F, R>(f: F) -> Result<Query<R>, ()>
-    where
-        F: Fn(Query<Self::Kind>, Self::Kind) -> Query<R>,
-    {
-        let qs = Query::<Self::Kind>::from_()?;
+            // `F` is taken by the tuple elements, and std only has `Default` up to 12-tuples
+            fn from_by<Func, R>(f: Func) -> Result<Query<R>, ()>
+            where
+                Func: Fn(Query<Self::Kind>, Self::Kind) -> Query<R>,
+            {
+                let qs = Query::<Self::Kind>::from_()?;
 
-        Ok(f(qs, Self::Kind::default()))
-    }
+                Ok(f(qs, ($($t::default(),)+)))
+            }
+        }
+    };
 }
 
+for_each_tuple!(from_query_tuple);
+
 impl<A, B> Default for InnerJoin<A, B>
 where
     A: Default + HasQuery<T = A>,
@@ -480,3 +538,61 @@ pub fn combine_joins(fs: &[FromClause], acc: &mut [FromClause]) -> Result<Vec<Fr
         }
     }
 }
+
+// The tables of a FROM and the ON conditions of its joins, for statements
+// that take their tables as a list. Only inner joins keep their meaning
+// that way, so any other join is an error.
+pub fn flatten_joins(clause: &[FromClause]) -> Result<(Vec<String>, Vec<String>), ()> {
+    let mut tables = vec![];
+    let mut conds = vec![];
+
+    for f in combine_joins(clause, &mut [])?.iter() {
+        flatten_from(f, &mut tables, &mut conds)?;
+    }
+
+    Ok((tables, conds))
+}
+
+// Every table of a FROM in the order declared, whatever joins it
+pub fn from_tables(clause: &[FromClause]) -> Result<Vec<String>, ()> {
+    fn push_tables(f: &FromClause, tables: &mut Vec<String>) {
+        match f {
+            FromClause::Start(t) => tables.push(t.clone()),
+            FromClause::Join(lhs, _, rhs, _) => {
+                push_tables(lhs, tables);
+                push_tables(rhs, tables);
+            }
+            FromClause::OnClause(_) => {}
+        }
+    }
+
+    let mut tables = vec![];
+
+    // combine_joins puts the last item of a tuple first
+    for f in combine_joins(clause, &mut [])?.iter().rev() {
+        push_tables(f, &mut tables);
+    }
+
+    Ok(tables)
+}
+
+fn flatten_from(f: &FromClause, tables: &mut Vec<String>, conds: &mut Vec<String>) -> Result<(), ()> {
+    match f {
+        FromClause::Start(t) => tables.push(t.clone()),
+        FromClause::Join(lhs, kind, rhs, on) => {
+            if !matches!(kind, JoinKind::InnerJoinKind) {
+                return Err(());
+            }
+
+            flatten_from(lhs, tables, conds)?;
+            flatten_from(rhs, tables, conds)?;
+
+            if let Some(on) = on {
+                conds.push(on.to_sql());
+            }
+        }
+        FromClause::OnClause(_) => {}
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::expression::never_;
+use crate::macros::for_each_tuple;
 use crate::query::UnsafeSqlFunctionArgument;
 use crate::types::{HasValue, ToLiteral};
 use std::rc::Rc;
@@ -25,63 +26,18 @@ where
     }
 }
 
-impl<A, B> UnsafeSqlFunctionArgument for (A, B)
-where
-    A: UnsafeSqlFunctionArgument,
-    B: UnsafeSqlFunctionArgument,
-{
-    fn to_arg_list(v: &(A, B)) -> Vec<Rc<dyn HasValue<bool, Output = bool>>> {
-        let mut a = UnsafeSqlFunctionArgument::to_arg_list(&v.0);
-        let mut b = UnsafeSqlFunctionArgument::to_arg_list(&v.1);
-        let mut result = vec![];
-
-        result.append(&mut a);
-        result.append(&mut b);
-
-        result
-    }
-}
-
-impl<A, B, C> UnsafeSqlFunctionArgument for (A, B, C)
-where
-    A: UnsafeSqlFunctionArgument,
-    B: UnsafeSqlFunctionArgument,
-    C: UnsafeSqlFunctionArgument,
-{
-    fn to_arg_list(v: &(A, B, C)) -> Vec<Rc<dyn HasValue<bool, Output = bool>>> {
-        let mut a = UnsafeSqlFunctionArgument::to_arg_list(&v.0);
-        let mut b = UnsafeSqlFunctionArgument::to_arg_list(&v.1);
-        let mut c = UnsafeSqlFunctionArgument::to_arg_list(&v.2);
-        let mut result = vec![];
+macro_rules! function_argument_tuple {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t: UnsafeSqlFunctionArgument),+> UnsafeSqlFunctionArgument for ($($t,)+) {
+            fn to_arg_list(v: &($($t,)+)) -> Vec<Rc<dyn HasValue<bool, Output = bool>>> {
+                let mut result = vec![];
 
-        result.append(&mut a);
-        result.append(&mut b);
-        result.append(&mut c);
+                $(result.append(&mut UnsafeSqlFunctionArgument::to_arg_list(&v.$i));)+
 
-        result
-    }
+                result
+            }
+        }
+    };
 }
 
-impl<A, B, C, D> UnsafeSqlFunctionArgument for (A, B, C, D)
-where
-    A: UnsafeSqlFunctionArgument,
-    B: UnsafeSqlFunctionArgument,
-    C: UnsafeSqlFunctionArgument,
-    D: UnsafeSqlFunctionArgument,
-{
-    fn to_arg_list(v: &(A, B, C, D)) -> Vec<Rc<dyn HasValue<bool, Output = bool>>> {
-        let mut result = vec![];
-
-        let mut a = UnsafeSqlFunctionArgument::to_arg_list(&v.0);
-        let mut b = UnsafeSqlFunctionArgument::to_arg_list(&v.1);
-        let mut c = UnsafeSqlFunctionArgument::to_arg_list(&v.2);
-        let mut d = UnsafeSqlFunctionArgument::to_arg_list(&v.3);
-
-        result.append(&mut a);
-        result.append(&mut b);
-        result.append(&mut c);
-        result.append(&mut d);
-
-        result
-    }
-}
+for_each_tuple!(function_argument_tuple);
@@ -1,12 +1,117 @@
-use crate::entity::HasEntityDef;
+use crate::dialect::*;
+use crate::entity::{Column as CL, HasEntityDef, Insertable};
+use crate::expression::never_;
 use crate::query::*;
 
+impl<A: Insertable> Query<A> {
+    pub fn entity_value_(mut self, a: &A) -> Query<A> {
+        let (columns, rows) = entity_rows(std::slice::from_ref(a)).remove(0);
+
+        for (column, value) in columns.into_iter().zip(rows[0].iter()) {
+            let set: SetValue<(), CL> = SetValue(never_(column), never_(value.as_str()));
+            self.state_mut().set_clause.push(Rc::new(set));
+        }
+        self
+    }
+
+    pub fn entity_values_(mut self, a: &[A]) -> Query<A> {
+        self.state_mut().values_clause = Some(Rc::new(RawValues(entity_rows(a))));
+        self
+    }
+}
+
+// Unset values are DEFAULT. SQLite has no DEFAULT in VALUES, so there rows
+// are grouped by the columns they set, in the order each group first
+// appears. Each group is its own INSERT, see `BulkInsert::chunks`.
+fn entity_rows<A: Insertable>(a: &[A]) -> Vec<ValuesGroup> {
+    let auto = A::auto_columns();
+    let columns = A::columns().into_iter().filter(|c| !auto.contains(c)).collect::<Vec<_>>();
+    let rows = a
+        .iter()
+        .map(|a| {
+            let values = a.insert_values();
+            columns
+                .iter()
+                .map(|c| values.iter().find(|(name, _)| name == c).and_then(|(_, v)| v.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    if dialect() != Dialect::Sqlite {
+        let names = columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>();
+        let values = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| v.unwrap_or_else(|| "DEFAULT".to_string())).collect())
+            .collect();
+
+        return vec![(names, values)];
+    }
+
+    let mut groups: Vec<(Vec<bool>, ValuesGroup)> = vec![];
+
+    for row in rows {
+        let set = row.iter().map(Option::is_some).collect::<Vec<_>>();
+        let values = row.into_iter().flatten().collect::<Vec<_>>();
+
+        match groups.iter_mut().find(|(s, _)| *s == set) {
+            Some((_, (_, rows))) => rows.push(values),
+            None => {
+                let names = columns.iter().zip(set.iter()).filter(|(_, s)| **s).map(|(c, _)| quote_ident(c)).collect();
+                groups.push((set, (names, vec![values])));
+            }
+        }
+    }
+
+    groups.into_iter().map(|(_, g)| g).collect()
+}
+
+impl<A> OnConflict<A> {
+    pub fn do_nothing_(self) -> Query<A> {
+        self.action(ConflictAction::Nothing)
+    }
+
+    pub fn do_update_(self, sets: Vec<Rc<dyn HasSet>>) -> Query<A> {
+        self.action(ConflictAction::Update(sets, WhereClause::No))
+    }
+
+    pub fn do_update_where_(self, sets: Vec<Rc<dyn HasSet>>, cond: Rc<dyn HasValue<bool, Output = bool>>) -> Query<A> {
+        self.action(ConflictAction::Update(sets, WhereClause::Where(cond)))
+    }
+
+    fn action(self, action: ConflictAction) -> Query<A> {
+        let OnConflict(mut q, target) = self;
+        q.state_mut().conflict_clause = Some(ConflictClause(target, action));
+        q
+    }
+}
+
+fn make_insert(clause: &Option<ConflictClause>) -> String {
+    match clause {
+        Some(ConflictClause(_, ConflictAction::Nothing)) if dialect() == Dialect::Mysql => String::from("INSERT IGNORE INTO "),
+        _ => String::from("INSERT INTO "),
+    }
+}
+
+fn make_conflict(clause: &Option<ConflictClause>) -> Result<String, ()> {
+    match clause {
+        Some(c) => {
+            let s = c.to_string();
+            if s.is_empty() {
+                Err(())
+            } else {
+                Ok(s)
+            }
+        }
+        None => Err(()),
+    }
+}
+
 impl<A> InsertInto<A>
 where
     A: HasEntityDef,
 {
     fn make_table(&self) -> Result<String, ()> {
-        Ok(A::table_name().name())
+        Ok(A::table_name().qualified())
     }
 
     fn make_column(&self, clause: &Vec<SetClause>) -> Result<String, ()> {
@@ -36,25 +141,36 @@ where
 
 impl<A: HasEntityDef> ToSql for InsertInto<A> {
     fn to_sql(&self) -> String {
-        let mut sql = String::from("INSERT INTO ");
-        let state = self.0.state.borrow();
+        let state = &self.0.state;
+        let mut sql = make_insert(&state.conflict_clause);
 
         if let Ok(a) = self.make_table() {
             sql = sql + &a;
         }
 
-        if let Ok(a) = self.make_column(&state.set_clause) {
-            sql = sql + "(" + &a + ")";
-        }
+        if state.set_clause.is_empty() && dialect() != Dialect::Mysql {
+            sql += " DEFAULT VALUES";
+        } else {
+            if let Ok(a) = self.make_column(&state.set_clause) {
+                sql = sql + "(" + &a + ")";
+            }
 
-        if let Ok(a) = self.make_values(&state.set_clause) {
-            sql = sql + " VALUES " + "(" + &a + ")";
+            if let Ok(a) = self.make_values(&state.set_clause) {
+                sql = sql + " VALUES " + "(" + &a + ")";
+            }
         }
 
         if let Ok(a) = self.make_duplicate(&state.duplicate_clause) {
             sql = sql + " ON DUPLICATE KEY UPDATE " + &a;
         }
 
+        if let Ok(a) = make_conflict(&state.conflict_clause) {
+            sql = sql + " " + &a;
+        }
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
+        }
+
         sql
     }
 }
@@ -65,7 +181,7 @@ where
     B: HasSelect,
 {
     fn make_table(&self) -> Result<String, ()> {
-        Ok(A::table_name().name())
+        Ok(A::table_name().qualified())
     }
 
     fn make_column(&self, clause: &Vec<SetClause>) -> Result<String, ()> {
@@ -77,7 +193,7 @@ where
 impl<A: HasEntityDef, B: HasSelect> ToSql for InsertSelect<A, B> {
     fn to_sql(&self) -> String {
         let mut sql = String::from("INSERT INTO ");
-        let state = self.0.state.borrow();
+        let state = &self.0.state;
 
         if let Ok(a) = self.make_table() {
             sql = sql + &a;
@@ -87,7 +203,13 @@ impl<A: HasEntityDef, B: HasSelect> ToSql for InsertSelect<A, B> {
             sql = sql + "(" + &a + ")";
         }
 
-        sql + " " + self.1.to_sql().as_ref()
+        sql = sql + " " + self.1.to_sql().as_ref();
+
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
+        }
+
+        sql
     }
 }
 
@@ -96,27 +218,13 @@ where
     A: HasEntityDef,
 {
     fn make_table(&self) -> Result<String, ()> {
-        Ok(A::table_name().name())
-    }
-
-    fn make_column(&self, values: &Box<dyn HasValues>) -> Result<String, ()> {
-        let c = values.columns();
-
-        if c.is_empty() {
-            return Err(());
-        }
-
-        Ok(c.join(", "))
+        Ok(A::table_name().qualified())
     }
 
-    fn make_values(&self, clause: &Box<dyn HasValues>) -> Result<String, ()> {
-        let values = clause
-            .values()
-            .iter()
-            .map(|v| "(".to_string() + &v.join(", ") + ")")
-            .collect::<Vec<String>>();
+    fn make_values(&self, rows: &[Vec<String>]) -> Result<String, ()> {
+        let values = rows.iter().map(|v| "(".to_string() + &v.join(", ") + ")").collect::<Vec<String>>();
 
-        Ok(values.join(" "))
+        Ok(values.join(", "))
     }
 
     fn make_duplicate(&self, clause: &Vec<DuplicateClause>) -> Result<String, ()> {
@@ -134,29 +242,103 @@ where
     }
 }
 
-impl<A: HasEntityDef> ToSql for BulkInsert<A> {
-    fn to_sql(&self) -> String {
-        let mut sql = String::from("INSERT INTO ");
-        let state = self.0.state.borrow();
+impl<A: HasEntityDef> BulkInsert<A> {
+    // `INSERT INTO t` and the clauses after the rows
+    fn make_statement(&self) -> (String, String) {
+        let state = &self.0.state;
+        let mut head = make_insert(&state.conflict_clause);
+        let mut tail = String::new();
 
         if let Ok(a) = self.make_table() {
-            sql = sql + &a;
+            head = head + &a;
         }
 
-        if let Some(clause) = &state.values_clause {
-            if let Ok(a) = self.make_column(clause) {
-                sql = sql + "(" + &a + ")";
+        if let Ok(a) = self.make_duplicate(&state.duplicate_clause) {
+            tail = tail + " ON DUPLICATE KEY UPDATE " + &a;
+        }
+
+        if let Ok(a) = make_conflict(&state.conflict_clause) {
+            tail = tail + " " + &a;
+        }
+
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            tail = tail + " RETURNING " + &a;
+        }
+
+        (head, tail)
+    }
+
+    // One statement per group of rows, split further by `limits`.
+    // A single row that is over the limits on its own still gets a statement.
+    pub fn chunks(&self, limits: &ChunkLimits) -> Vec<String> {
+        let (head, tail) = self.make_statement();
+        let groups = match &self.0.state.values_clause {
+            Some(clause) => clause.groups(),
+            None => return vec![head + &tail],
+        };
+
+        let mut stmts = vec![];
+
+        for (columns, rows) in groups {
+            // Only MySQL spells a row of defaults as `()`
+            if columns.is_empty() && dialect() != Dialect::Mysql {
+                stmts.extend(rows.iter().map(|_| format!("{} DEFAULT VALUES{}", head, tail)));
+                continue;
             }
 
-            if let Ok(a) = self.make_values(&clause) {
-                sql = sql + " VALUES " + &a;
+            let head = format!("{}({}) VALUES ", head, columns.join(", "));
+            let fixed = head.len() + tail.len();
+
+            let mut chunks = vec![];
+            let mut chunk: Vec<Vec<String>> = vec![];
+            let mut params = 0;
+            let mut bytes = fixed;
+
+            for row in rows {
+                // `(a, b)`, and the `, ` in front of it unless it starts a statement
+                let size = row.iter().map(|v| v.len() + 2).sum::<usize>();
+
+                if !chunk.is_empty() && limits.exceeded(chunk.len() + 1, params + row.len(), bytes + 2 + size) {
+                    chunks.push(std::mem::take(&mut chunk));
+                    params = 0;
+                    bytes = fixed;
+                }
+
+                params += row.len();
+                bytes += if chunk.is_empty() { size } else { size + 2 };
+                chunk.push(row);
+            }
+
+            if !chunk.is_empty() {
+                chunks.push(chunk);
             }
+
+            stmts.extend(
+                chunks
+                    .iter()
+                    .filter_map(|c| self.make_values(c).ok())
+                    .map(|values| head.clone() + &values + &tail),
+            );
         }
 
-        if let Ok(a) = self.make_duplicate(&state.duplicate_clause) {
-            sql = sql + " ON DUPLICATE KEY UPDATE " + &a;
+        stmts
+    }
+}
+
+impl<A: HasEntityDef> BulkInsert<A> {
+    // Err when the rows need more than one statement, e.g. SQLite rows that
+    // leave different columns to their defaults
+    pub(crate) fn single(self) -> Result<BulkInsert<A>, ()> {
+        if self.chunks(&ChunkLimits::default()).len() > 1 {
+            return Err(());
         }
 
-        sql
+        Ok(self)
+    }
+}
+
+impl<A: HasEntityDef> ToSql for BulkInsert<A> {
+    fn to_sql(&self) -> String {
+        self.chunks(&ChunkLimits::default()).into_iter().next().unwrap_or_default()
     }
 }
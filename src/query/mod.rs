@@ -1,11 +1,15 @@
-use std::cell::{Ref, RefCell};
 use std::rc::Rc;
 
-use crate::entity::HasEntityDef;
+use crate::dialect::{dialect, with_unqualified_columns, Dialect};
+use crate::entity::{HasEntityDef, HasTableDef, Insertable};
+use crate::format::{format_sql, FormatOptions};
+use crate::macros::for_each_tuple;
 use crate::types::*;
 
 mod column;
+mod ddl;
 mod delete;
+mod explain;
 mod from;
 mod functions;
 mod insert;
@@ -13,20 +17,44 @@ mod select;
 mod update;
 
 use self::column::*;
-use self::from::combine_joins;
+use self::from::{combine_joins, flatten_joins, from_tables};
 
 pub trait HasQuery {
     type T;
 }
 
+#[derive(Clone)]
 pub struct Query<A> {
-    pub state: Rc<RefCell<QueryState>>,
+    pub state: Rc<QueryState>,
     pub value: A,
 }
 
+// A named, reusable change to a query, e.g. "active users" or "within
+// tenant", applied with `q.scope_(active_users)`
+pub trait HasScope<A> {
+    fn apply(&self, q: Query<A>) -> Query<A>;
+}
+
+impl<A, F: Fn(Query<A>) -> Query<A>> HasScope<A> for F {
+    fn apply(&self, q: Query<A>) -> Query<A> {
+        self(q)
+    }
+}
+
+pub struct Scope<A>(Rc<dyn Fn(Query<A>) -> Query<A>>);
+
+// Scopes compose with `and_`, applied in order
+pub fn scope_<A, S: 'static + HasScope<A>>(s: S) -> Scope<A> {
+    Scope(Rc::new(move |q| s.apply(q)))
+}
+
 pub trait ToSql {
     fn to_sql(&self) -> String;
 
+    fn to_sql_with(&self, opts: &FormatOptions) -> String {
+        format_sql(&self.to_sql(), opts)
+    }
+
     fn make_where(&self, clause: &WhereClause) -> Result<String, ()> {
         match clause {
             WhereClause::No => Err(()),
@@ -75,6 +103,10 @@ pub trait ToSql {
             _ => Ok(clause.to_string()),
         }
     }
+
+    fn make_returning(&self, clause: &Option<String>) -> Result<String, ()> {
+        clause.clone().ok_or(())
+    }
 }
 
 pub trait FromQuery {
@@ -87,12 +119,12 @@ pub trait FromQuery {
 }
 
 pub trait HasSelect: ToSql {
-    fn get_state(&self) -> Ref<QueryState>;
+    fn get_state(&self) -> &QueryState;
 }
 
 impl<A: Column> HasSelect for Select<A> {
-    fn get_state(&self) -> Ref<QueryState> {
-        self.0.state.borrow()
+    fn get_state(&self) -> &QueryState {
+        &self.0.state
     }
 }
 
@@ -105,13 +137,32 @@ pub fn select<A: Column>(q: Query<A>) -> impl HasSelect {
 pub trait HasUpdate: ToSql {}
 
 pub struct Update<A>(Query<A>);
-impl<A: Column> HasUpdate for Update<A> {}
+impl<A: Column + HasEntityDef> HasUpdate for Update<A> {}
 
 pub struct UpdateSelect<A, B: HasSelect>(Query<A>, B);
 impl<A: HasEntityDef, B: HasSelect> HasUpdate for UpdateSelect<A, B> {}
 
-pub fn update<A: Column>(q: Query<A>) -> impl HasUpdate {
-    Update(q)
+// The entity `A` is the table updated, and has to be in the FROM.
+// MySQL takes no ORDER BY or LIMIT in an UPDATE of several tables, the
+// other dialects list the other tables in FROM, which only keeps inner joins.
+pub fn update<A: Column + HasEntityDef>(q: Query<A>) -> Result<impl HasUpdate, ()> {
+    let state = &q.state;
+    let ordered = !state.order_clause.is_empty() || !matches!(state.limit_clause, LimitClause::No);
+    let tables = from_tables(&state.from_clause)?;
+
+    if !tables.contains(&A::table_name().qualified()) {
+        return Err(());
+    }
+
+    match dialect() {
+        Dialect::Postgres | Dialect::Sqlite => {
+            flatten_joins(&state.from_clause)?;
+        }
+        _ if ordered && tables.len() > 1 => return Err(()),
+        _ => {}
+    }
+
+    Ok(Update(q))
 }
 
 pub fn update_select<A: Column, B, F>(q: Query<A>, f: F) -> UpdateSelect<B, impl HasSelect>
@@ -130,6 +181,8 @@ pub trait HasInsert: ToSql {}
 pub struct InsertInto<A>(Query<A>);
 impl<A: HasEntityDef> HasInsert for InsertInto<A> {}
 
+pub struct OnConflict<A>(Query<A>, ConflictTarget);
+
 pub trait ToValues {
     fn to_vec(&self) -> Vec<String>;
 }
@@ -140,19 +193,19 @@ impl<A, B: ToLiteral> ToValues for Rc<dyn HasValue<A, Output = B>> {
     }
 }
 
-impl<A, B, T1: ToLiteral, T2: ToLiteral> ToValues for (Rc<dyn HasValue<A, Output = T1>>, Rc<dyn HasValue<B, Output = T2>>) {
-    fn to_vec(&self) -> Vec<String> {
-        vec![self.0.to_sql(), self.1.to_sql()]
-    }
+macro_rules! to_values_tuple {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t: ToValues),+> ToValues for ($($t,)+) {
+            fn to_vec(&self) -> Vec<String> {
+                let mut result = vec![];
+                $(result.append(&mut self.$i.to_vec());)+
+                result
+            }
+        }
+    };
 }
 
-impl<A, B, C, T1: ToLiteral, T2: ToLiteral, T3: ToLiteral> ToValues
-    for (Rc<dyn HasValue<A, Output = T1>>, Rc<dyn HasValue<B, Output = T2>>, Rc<dyn HasValue<C, Output = T3>>)
-{
-    fn to_vec(&self) -> Vec<String> {
-        vec![self.0.to_sql(), self.1.to_sql(), self.2.to_sql()]
-    }
-}
+for_each_tuple!(to_values_tuple);
 
 pub struct BulkInsert<A>(Query<A>);
 impl<A: HasEntityDef> HasInsert for BulkInsert<A> {}
@@ -164,8 +217,62 @@ pub fn insert_into<A: HasEntityDef>(q: Query<A>) -> impl HasInsert {
     InsertInto(q)
 }
 
-pub fn bulk_insert<A: HasEntityDef>(q: Query<A>) -> impl HasInsert {
-    BulkInsert(q)
+// Rows that need several statements, see `BulkInsert::chunks`, are refused
+// here and only go through `bulk_insert_chunks`.
+pub fn bulk_insert<A: HasEntityDef>(q: Query<A>) -> Result<impl HasInsert, ()> {
+    BulkInsert(q).single()
+}
+
+pub fn bulk_insert_chunks<A: HasEntityDef>(q: Query<A>, limits: ChunkLimits) -> impl Iterator<Item = String> {
+    BulkInsert(q).chunks(&limits).into_iter()
+}
+
+// Upper bounds for one bulk INSERT statement, `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkLimits {
+    pub rows: Option<usize>,
+    pub params: Option<usize>,
+    pub bytes: Option<usize>,
+}
+
+// SQLITE_MAX_VARIABLE_NUMBER before and since SQLite 3.32
+pub const SQLITE_LEGACY_MAX_PARAMS: usize = 999;
+pub const SQLITE_MAX_PARAMS: usize = 32766;
+
+impl ChunkLimits {
+    pub fn for_dialect(d: Dialect) -> ChunkLimits {
+        let params = match d {
+            Dialect::Sqlite => Some(SQLITE_MAX_PARAMS),
+            Dialect::Postgres | Dialect::Mysql => Some(65535),
+            Dialect::Standard => None,
+        };
+
+        ChunkLimits { params, ..ChunkLimits::default() }
+    }
+
+    pub fn rows(self, n: usize) -> ChunkLimits {
+        ChunkLimits { rows: Some(n), ..self }
+    }
+
+    pub fn params(self, n: usize) -> ChunkLimits {
+        ChunkLimits { params: Some(n), ..self }
+    }
+
+    pub fn bytes(self, n: usize) -> ChunkLimits {
+        ChunkLimits { bytes: Some(n), ..self }
+    }
+
+    fn exceeded(&self, rows: usize, params: usize, bytes: usize) -> bool {
+        self.rows.is_some_and(|n| rows > n) || self.params.is_some_and(|n| params > n) || self.bytes.is_some_and(|n| bytes > n)
+    }
+}
+
+pub fn insert_into_values<A: Insertable + Default>(a: &A) -> impl HasInsert {
+    InsertInto(Query::new(A::default()).entity_value_(a))
+}
+
+pub fn bulk_insert_values<A: Insertable + Default>(a: &[A]) -> Result<impl HasInsert, ()> {
+    bulk_insert(Query::new(A::default()).entity_values_(a))
 }
 
 pub fn insert_select<A: Column, B, F>(q: Query<A>, f: F) -> InsertSelect<B, impl HasSelect>
@@ -188,11 +295,143 @@ pub fn delete<A: Column>(q: Query<A>) -> impl HasDelete {
     Delete(q)
 }
 
-pub struct Truncate<A>(Query<A>);
-impl<A: Column> HasDelete for Truncate<A> {}
+// DELETE of table `T` filtered by the other tables in the FROM
+pub struct DeleteUsing<T, A>(Query<A>, std::marker::PhantomData<T>);
+impl<T: HasEntityDef, A> HasDelete for DeleteUsing<T, A> {}
+
+// Outer joins would turn into inner joins once the tables are listed in USING
+pub fn delete_using<T: HasEntityDef, A>(q: Query<A>) -> Result<impl HasDelete, ()> {
+    flatten_joins(&q.state.from_clause)?;
+
+    Ok(DeleteUsing(q, std::marker::PhantomData::<T>))
+}
+
+// Not a `ToSql`, MySQL and SQLite need several statements, see `Truncate::statements`
+pub struct Truncate<A>(Query<A>, Option<Identity>, Option<DropBehavior>);
+
+// Every table in the FROM is truncated, e.g. `Query::<(User, Library)>::from_()`
+pub fn truncate<A: Column>(q: Query<A>) -> Result<Truncate<A>, ()> {
+    if from_tables(&q.state.from_clause)?.is_empty() {
+        return Err(());
+    }
+
+    Ok(Truncate(q, None, None))
+}
+
+pub struct CreateTable<A>(bool, Vec<ConstraintClause>, std::marker::PhantomData<A>);
+
+pub fn create_table<A: HasTableDef>() -> CreateTable<A> {
+    CreateTable(false, vec![], std::marker::PhantomData)
+}
+
+pub struct DropTable<A>(bool, Option<DropBehavior>, std::marker::PhantomData<A>);
+
+pub fn drop_table<A: HasEntityDef>() -> DropTable<A> {
+    DropTable(false, None, std::marker::PhantomData)
+}
+
+// ANALYZE, VERBOSE, FORMAT JSON and the dialect the statement was built under
+pub struct Explain<S>(S, bool, bool, bool, Dialect);
+
+pub fn explain_<S: ToSql>(stmt: S) -> Explain<S> {
+    Explain(stmt, false, false, false, dialect())
+}
+
+// Columns or expressions of an index, `desc_` marks a descending one
+pub trait IndexColumn {
+    fn index_columns(&self) -> Vec<String>;
+}
+
+impl<A, B: ToLiteral> IndexColumn for Rc<dyn HasValue<A, Output = B>> {
+    fn index_columns(&self) -> Vec<String> {
+        vec![self.to_sql()]
+    }
+}
+
+impl IndexColumn for Rc<dyn HasOrder> {
+    fn index_columns(&self) -> Vec<String> {
+        vec![self.to_string()]
+    }
+}
+
+macro_rules! index_column_tuple {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t: IndexColumn),+> IndexColumn for ($($t,)+) {
+            fn index_columns(&self) -> Vec<String> {
+                let mut result = vec![];
+                $(result.append(&mut self.$i.index_columns());)+
+                result
+            }
+        }
+    };
+}
+
+for_each_tuple!(index_column_tuple);
+
+pub struct CreateIndex<A> {
+    name: String,
+    columns: Vec<String>,
+    unique: bool,
+    if_not_exists: bool,
+    where_clause: Option<String>,
+    _entity: std::marker::PhantomData<A>,
+}
+
+// create_index_::<User, _>("user_email", |u| (u.email(), desc_(u.user_id())))
+pub fn create_index_<A: HasEntityDef + Default, C: IndexColumn>(name: &str, f: impl Fn(A) -> C) -> CreateIndex<A> {
+    CreateIndex {
+        name: name.to_string(),
+        columns: with_unqualified_columns(|| f(A::default()).index_columns()),
+        unique: false,
+        if_not_exists: false,
+        where_clause: None,
+        _entity: std::marker::PhantomData,
+    }
+}
+
+pub struct DropIndex<A>(String, bool, std::marker::PhantomData<A>);
+
+pub fn drop_index_<A: HasEntityDef>(name: &str) -> DropIndex<A> {
+    DropIndex(name.to_string(), false, std::marker::PhantomData)
+}
+
+pub struct ForeignKey {
+    name: Option<String>,
+    columns: Vec<String>,
+    table: String,
+    references: Vec<String>,
+    on_delete: Option<ReferentialAction>,
+    on_update: Option<ReferentialAction>,
+}
+
+// foreign_key_(|m: Membership, l: Library| (m.library_id(), l.library_id()))
+pub fn foreign_key_<A, B, C, D>(f: impl Fn(A, B) -> (C, D)) -> ForeignKey
+where
+    A: HasEntityDef + Default,
+    B: HasEntityDef + Default,
+    C: IndexColumn,
+    D: IndexColumn,
+{
+    let (columns, references) = with_unqualified_columns(|| {
+        let (columns, references) = f(A::default(), B::default());
+        (columns.index_columns(), references.index_columns())
+    });
+
+    ForeignKey {
+        name: None,
+        columns,
+        table: B::table_name().qualified(),
+        references,
+        on_delete: None,
+        on_update: None,
+    }
+}
+
+pub struct Check(Option<String>, String);
 
-pub fn truncate<A: Column>(q: Query<A>) -> impl HasDelete {
-    Truncate(q)
+// check_(|a: Account| gt_(&a.account_id(), &val_(0)))
+pub fn check_<A: HasEntityDef + Default>(f: impl Fn(A) -> Rc<dyn HasValue<bool, Output = bool>>) -> Check {
+    Check(None, with_unqualified_columns(|| f(A::default()).to_sql()))
 }
 
 pub trait UnsafeSqlFunctionArgument {
@@ -21,9 +21,6 @@ impl<A: Column> ToSql for Select<A> {
         if let Ok(a) = self.make_where(&state.where_clause) {
             sql = sql + " WHERE " + &a;
         }
-        if let Ok(a) = self.make_order(&state.order_clause) {
-            sql = sql + " ORDER BY " + &a;
-        }
         if let Ok(a) = self.make_group(&state.groupby_clause) {
             sql = sql + " GROUP BY " + &a;
         
@@ -31,6 +28,9 @@ impl<A: Column> ToSql for Select<A> {
                 sql = sql + " HAVING " + &a;
             }
         }
+        if let Ok(a) = self.make_order(&state.order_clause) {
+            sql = sql + " ORDER BY " + &a;
+        }
         if let Ok(a) = self.make_limit(&state.limit_clause) {
             sql = sql + " " + &a;
         }
@@ -1,3 +1,5 @@
+use crate::dialect::*;
+use crate::entity::column_name;
 use crate::query::*;
 
 impl<A: Column> Update<A> {
@@ -13,21 +15,99 @@ impl<A: Column> Update<A> {
     }
 }
 
-impl<A: Column> ToSql for Update<A> {
-    fn to_sql(&self) -> String {
-        let mut sql = String::from("UPDATE");
-        let state = self.0.state.borrow();
+impl<A: Column + HasEntityDef> Update<A> {
+    // PostgreSQL and SQLite take the other tables in FROM, and ORDER BY / LIMIT
+    // through a subquery on the row key of the target table.
+    fn make_keyed(&self, state: &QueryState, key: &str) -> String {
+        let target = A::table_name().qualified();
+        // checked by `update`
+        let (mut tables, mut conds) = flatten_joins(&state.from_clause).unwrap_or_default();
 
-        if let Ok(a) = self.make_from(&state.from_clause) {
-            sql = sql + " " + &a;
+        if let Some(i) = tables.iter().position(|t| *t == target) {
+            tables.remove(i);
         }
 
-        if let Ok(a) = self.make_set(&state.set_clause) {
-            sql = sql + " SET " + &a;
+        let mut sql = String::from("UPDATE ") + &target;
+
+        let sets = state.set_clause.iter().map(|s| format!("{} = {}", column_name(&s.column()), s.value())).collect::<Vec<_>>();
+        if !sets.is_empty() {
+            sql = sql + " SET " + &sets.join(", ");
         }
-        
+
+        if !tables.is_empty() {
+            sql = sql + " FROM " + &tables.join(", ");
+        }
+
         if let Ok(a) = self.make_where(&state.where_clause) {
-            sql = sql + " WHERE " + &a;
+            conds.push(a);
+        }
+
+        let order = self.make_order(&state.order_clause);
+        let limit = self.make_limit(&state.limit_clause);
+
+        if order.is_ok() || limit.is_ok() {
+            let mut sub = format!("SELECT {}.{}", target, key);
+
+            if let Ok(a) = self.make_from(&state.from_clause) {
+                sub = sub + " FROM " + &a;
+            }
+            if let Ok(a) = self.make_where(&state.where_clause) {
+                sub = sub + " WHERE " + &a;
+            }
+            if let Ok(a) = order {
+                sub = sub + " ORDER BY " + &a;
+            }
+            if let Ok(a) = limit {
+                sub = sub + " " + &a;
+            }
+
+            conds.push(format!("{}.{} IN ({})", target, key, sub));
+        }
+
+        if !conds.is_empty() {
+            sql = sql + " WHERE " + &conds.join(" AND ");
+        }
+
+        sql
+    }
+}
+
+impl<A: Column + HasEntityDef> ToSql for Update<A> {
+    fn to_sql(&self) -> String {
+        let state = &self.0.state;
+
+        let mut sql = match dialect() {
+            Dialect::Postgres => self.make_keyed(state, "ctid"),
+            Dialect::Sqlite => self.make_keyed(state, "rowid"),
+            _ => {
+                let mut sql = String::from("UPDATE");
+
+                if let Ok(a) = self.make_from(&state.from_clause) {
+                    sql = sql + " " + &a;
+                }
+
+                if let Ok(a) = self.make_set(&state.set_clause) {
+                    sql = sql + " SET " + &a;
+                }
+
+                if let Ok(a) = self.make_where(&state.where_clause) {
+                    sql = sql + " WHERE " + &a;
+                }
+
+                if let Ok(a) = self.make_order(&state.order_clause) {
+                    sql = sql + " ORDER BY " + &a;
+                }
+
+                if let Ok(a) = self.make_limit(&state.limit_clause) {
+                    sql = sql + " " + &a;
+                }
+
+                sql
+            }
+        };
+
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
         }
 
         sql
@@ -40,7 +120,7 @@ where
     B: HasSelect,
 {
     fn make_table(&self) -> Result<String, ()> {
-        Ok(A::table_name().name())
+        Ok(A::table_name().qualified())
     }
 
     fn make_set(&self, clause: &Vec<SetClause>) -> Result<String, ()> {
@@ -57,7 +137,7 @@ where
 impl<A: HasEntityDef, B: HasSelect> ToSql for UpdateSelect<A, B> {
     fn to_sql(&self) -> String {
         let mut sql = String::from("UPDATE ");
-        let state = self.0.state.borrow();
+        let state = &self.0.state;
         let select_state = self.1.get_state();
 
         if let Ok(a) = self.make_table() {
@@ -79,6 +159,9 @@ impl<A: HasEntityDef, B: HasSelect> ToSql for UpdateSelect<A, B> {
         if let Ok(a) = self.make_limit(&select_state.limit_clause) {
             sql = sql + " " + &a;
         }
+        if let Ok(a) = self.make_returning(&state.returning_clause) {
+            sql = sql + " RETURNING " + &a;
+        }
 
         sql
     }
@@ -0,0 +1,446 @@
+use crate::dialect::*;
+use crate::entity::{ColumnDef, Table};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Column of a table with its type resolved for one dialect
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColumnSchema {
+    pub name: String,
+    pub sql_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+    pub unique: bool,
+    pub auto_increment: bool,
+}
+
+impl ColumnSchema {
+    pub fn new(c: &ColumnDef) -> ColumnSchema {
+        ColumnSchema {
+            name: c.name.clone(),
+            sql_type: c.type_name().to_string(),
+            nullable: c.nullable,
+            default: c.default.clone(),
+            primary_key: c.primary_key,
+            unique: c.unique,
+            auto_increment: c.auto_increment,
+        }
+    }
+
+    // Column definition, the primary key is inlined unless it is composite
+    pub fn to_sql(&self, inline_key: bool) -> String {
+        let d = dialect();
+        let mut sql = format!("{} {}", quote_ident(&self.name), self.sql_type);
+
+        if !self.nullable {
+            sql += " NOT NULL";
+        }
+
+        if let Some(v) = &self.default {
+            sql = sql + " DEFAULT " + v;
+        }
+
+        if self.auto_increment {
+            match d {
+                Dialect::Mysql => sql += " AUTO_INCREMENT",
+                Dialect::Postgres | Dialect::Standard => sql += " GENERATED BY DEFAULT AS IDENTITY",
+                // SQLite only has AUTOINCREMENT on an INTEGER PRIMARY KEY
+                Dialect::Sqlite => {}
+            }
+        }
+
+        if self.primary_key && inline_key {
+            sql += " PRIMARY KEY";
+
+            if self.auto_increment && d == Dialect::Sqlite {
+                sql += " AUTOINCREMENT";
+            }
+        } else if self.unique {
+            sql += " UNIQUE";
+        }
+
+        sql
+    }
+
+    // Keys are table constraints once the table exists
+    fn without_keys(&self) -> ColumnSchema {
+        ColumnSchema {
+            primary_key: false,
+            unique: false,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnSchema>,
+    pub constraints: Vec<String>,
+}
+
+impl TableSchema {
+    fn table(&self) -> Table {
+        Table::new(&self.name, None)
+    }
+
+    // Name without the schema, as in the names the database gives constraints
+    fn last_name(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or_default()
+    }
+
+    fn primary_key(&self) -> Vec<String> {
+        self.columns.iter().filter(|c| c.primary_key).map(|c| quote_ident(&c.name)).collect()
+    }
+
+    // Columns declared UNIQUE, which a single column primary key is not
+    fn unique_columns(&self) -> Vec<&str> {
+        let inline_key = self.primary_key().len() == 1;
+
+        self.columns.iter().filter(|c| c.unique && !(c.primary_key && inline_key)).map(|c| c.name.as_str()).collect()
+    }
+
+    pub fn create_sql(&self, if_not_exists: bool) -> String {
+        let keys = self.primary_key();
+
+        // A composite key is declared as a table constraint
+        let mut defs = self.columns.iter().map(|c| c.to_sql(keys.len() == 1)).collect::<Vec<_>>();
+        if keys.len() > 1 {
+            defs.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+        }
+
+        defs.extend(self.constraints.iter().cloned());
+
+        let mut sql = String::from("CREATE TABLE ");
+
+        if if_not_exists {
+            sql += "IF NOT EXISTS ";
+        }
+
+        format!("{}{} ({})", sql, self.table().qualified(), defs.join(", "))
+    }
+
+    fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexSchema {
+    pub name: String,
+    pub table: String,
+    pub sql: String,
+}
+
+// Tables and indexes of a database, e.g. the entities compiled into the
+// binary or a snapshot checked in next to the migrations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Schema {
+    pub dialect: Dialect,
+    pub tables: Vec<TableSchema>,
+    pub indexes: Vec<IndexSchema>,
+}
+
+impl Default for Schema {
+    fn default() -> Schema {
+        Schema::new()
+    }
+}
+
+impl Schema {
+    // Types and literals are rendered for the current dialect
+    pub fn new() -> Schema {
+        Schema {
+            dialect: dialect(),
+            tables: vec![],
+            indexes: vec![],
+        }
+    }
+
+    pub fn table(self, t: TableSchema) -> Schema {
+        let mut tables = self.tables;
+        tables.push(t);
+
+        Schema { tables, ..self }
+    }
+
+    pub fn index(self, i: IndexSchema) -> Schema {
+        let mut indexes = self.indexes;
+        indexes.push(i);
+
+        Schema { indexes, ..self }
+    }
+
+    fn find_table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    // Statements migrating `self` to `to`, in the dialect of `to`. Indexes
+    // are dropped first and created last, tables are created in the order
+    // they were added and dropped in reverse. Fails when a change cannot be
+    // migrated, such as dropping a constraint without a name outside SQLite.
+    pub fn diff(&self, to: &Schema) -> Result<Vec<String>, ()> {
+        with_dialect(to.dialect, || {
+            let mut stmts = vec![];
+            let mut rebuilt = vec![];
+            let mut altered = vec![];
+
+            for new in to.tables.iter() {
+                match self.find_table(&new.name) {
+                    None => altered.push(new.create_sql(false)),
+                    Some(old) if old == new => {}
+                    Some(old) if dialect() == Dialect::Sqlite => match alter_sqlite(old, new) {
+                        Some(mut a) => altered.append(&mut a),
+                        None => {
+                            altered.append(&mut rebuild_table(old, new)?);
+                            rebuilt.push(new.name.clone());
+                        }
+                    },
+                    Some(old) => altered.append(&mut alter_table(old, new)?),
+                }
+            }
+
+            for old in self.indexes.iter() {
+                let dropped_table = to.find_table(&old.table).is_none();
+
+                if !dropped_table && !to.indexes.contains(old) {
+                    stmts.push(drop_index(old));
+                }
+            }
+
+            stmts.append(&mut altered);
+
+            for old in self.tables.iter().rev() {
+                if to.find_table(&old.name).is_none() {
+                    stmts.push(format!("DROP TABLE {}", old.table().qualified()));
+                }
+            }
+
+            // Rebuilding a table drops its indexes
+            for new in to.indexes.iter() {
+                if !self.indexes.contains(new) || rebuilt.contains(&new.table) {
+                    stmts.push(new.sql.clone());
+                }
+            }
+
+            Ok(stmts)
+        })
+    }
+}
+
+fn drop_index(i: &IndexSchema) -> String {
+    match dialect() {
+        Dialect::Mysql => format!("DROP INDEX {} ON {}", quote_ident(&i.name), Table::new(&i.table, None).qualified()),
+        _ => format!("DROP INDEX {}", quote_ident(&i.name)),
+    }
+}
+
+// `CONSTRAINT name ...`, other constraints are named by the database
+fn constraint_name(sql: &str) -> Option<&str> {
+    let mut words = sql.split_whitespace();
+
+    match words.next() {
+        Some(w) if w.eq_ignore_ascii_case("CONSTRAINT") => words.next(),
+        _ => None,
+    }
+}
+
+// ALTER TABLE statements for PostgreSQL and MySQL. Constraints and keys are
+// dropped first and added last, so the columns they cover exist.
+fn alter_table(old: &TableSchema, new: &TableSchema) -> Result<Vec<String>, ()> {
+    let d = dialect();
+    let table = new.table().qualified();
+    let alter = |a: String| format!("ALTER TABLE {} {}", table, a);
+
+    // MySQL only takes AUTO_INCREMENT on a key column, so it is removed
+    // before the keys are dropped and set after they are added
+    let mut first = vec![];
+    let mut drops = vec![];
+    let mut columns = vec![];
+    let mut adds = vec![];
+
+    for c in old.constraints.iter().filter(|c| !new.constraints.contains(c)) {
+        drops.push(alter(format!("DROP CONSTRAINT {}", constraint_name(c).ok_or(())?)));
+    }
+
+    // PostgreSQL names an inline UNIQUE `<table>_<column>_key`, MySQL after the column
+    let (old_unique, new_unique) = (old.unique_columns(), new.unique_columns());
+
+    for c in old_unique.iter().filter(|c| !new_unique.contains(c)) {
+        drops.push(alter(match d {
+            Dialect::Mysql => format!("DROP INDEX {}", quote_ident(c)),
+            _ => format!("DROP CONSTRAINT {}", quote_ident(&format!("{}_{}_key", old.last_name(), c))),
+        }));
+    }
+
+    let (old_keys, new_keys) = (old.primary_key(), new.primary_key());
+
+    if old_keys != new_keys {
+        let drop_key = match d {
+            Dialect::Mysql => "DROP PRIMARY KEY".to_string(),
+            _ => format!("DROP CONSTRAINT {}", quote_ident(&format!("{}_pkey", old.last_name()))),
+        };
+        let add_key = format!("ADD PRIMARY KEY ({})", new_keys.join(", "));
+
+        match (old_keys.is_empty(), new_keys.is_empty()) {
+            // MySQL swaps the key in one statement, an AUTO_INCREMENT column cannot be without one
+            (false, false) if d == Dialect::Mysql => adds.push(alter(format!("{}, {}", drop_key, add_key))),
+            (false, false) => {
+                drops.push(alter(drop_key));
+                adds.push(alter(add_key));
+            }
+            (false, true) => drops.push(alter(drop_key)),
+            (true, _) => adds.push(alter(add_key)),
+        }
+    }
+
+    for c in new_unique.iter().filter(|c| !old_unique.contains(c)) {
+        adds.push(alter(format!("ADD UNIQUE ({})", quote_ident(c))));
+    }
+
+    for c in new.constraints.iter().filter(|c| !old.constraints.contains(c)) {
+        adds.push(alter(format!("ADD {}", c)));
+    }
+
+    for c in old.columns.iter() {
+        if new.column(&c.name).is_none() {
+            columns.push(alter(format!("DROP COLUMN {}", quote_ident(&c.name))));
+        }
+    }
+
+    for c in new.columns.iter() {
+        match old.column(&c.name) {
+            None if d == Dialect::Mysql && c.auto_increment => {
+                let c = c.without_keys();
+                let added = ColumnSchema {
+                    auto_increment: false,
+                    ..c.clone()
+                };
+
+                columns.push(alter(format!("ADD COLUMN {}", added.to_sql(false))));
+                adds.push(alter(format!("MODIFY COLUMN {}", c.to_sql(false))));
+            }
+            None => columns.push(alter(format!("ADD COLUMN {}", c.without_keys().to_sql(false)))),
+            Some(o) if o.without_keys() == c.without_keys() => {}
+            // MODIFY restates the whole column, its keys are left as they are
+            Some(o) if d == Dialect::Mysql => {
+                let modify = alter(format!("MODIFY COLUMN {}", c.without_keys().to_sql(false)));
+
+                match (o.auto_increment, c.auto_increment) {
+                    (true, false) => first.push(modify),
+                    (false, true) => adds.push(modify),
+                    _ => columns.push(modify),
+                }
+            }
+            Some(o) => {
+                let column = alter(format!("ALTER COLUMN {}", quote_ident(&c.name)));
+
+                if o.sql_type != c.sql_type {
+                    columns.push(format!("{} TYPE {}", column, c.sql_type));
+                }
+
+                if o.nullable != c.nullable {
+                    let op = if c.nullable { "DROP" } else { "SET" };
+                    columns.push(format!("{} {} NOT NULL", column, op));
+                }
+
+                if o.default != c.default {
+                    match &c.default {
+                        Some(v) => columns.push(format!("{} SET DEFAULT {}", column, v)),
+                        None => columns.push(format!("{} DROP DEFAULT", column)),
+                    }
+                }
+
+                if o.auto_increment != c.auto_increment {
+                    match c.auto_increment {
+                        true => columns.push(format!("{} ADD GENERATED BY DEFAULT AS IDENTITY", column)),
+                        false => columns.push(format!("{} DROP IDENTITY", column)),
+                    }
+                }
+            }
+        }
+    }
+
+    first.append(&mut drops);
+    first.append(&mut columns);
+    first.append(&mut adds);
+
+    Ok(first)
+}
+
+// The columns SQLite can add and drop in place, `None` when the table has to
+// be rebuilt instead
+fn alter_sqlite(old: &TableSchema, new: &TableSchema) -> Option<Vec<String>> {
+    let table = new.table().qualified();
+    let mut stmts = vec![];
+
+    if old.constraints != new.constraints {
+        return None;
+    }
+
+    for c in old.columns.iter() {
+        if new.column(&c.name).is_none() {
+            // SQLite cannot drop key columns
+            if c.primary_key || c.unique {
+                return None;
+            }
+
+            stmts.push(format!("ALTER TABLE {} DROP COLUMN {}", table, quote_ident(&c.name)));
+        }
+    }
+
+    for c in new.columns.iter() {
+        match old.column(&c.name) {
+            // SQLite adds neither key columns nor NOT NULL ones without a default
+            None if c.primary_key || c.auto_increment || c.unique || (!c.nullable && c.default.is_none()) => return None,
+            None => stmts.push(format!("ALTER TABLE {} ADD COLUMN {}", table, c.to_sql(false))),
+            Some(o) if o == c => {}
+            Some(_) => return None,
+        }
+    }
+
+    Some(stmts)
+}
+
+// Creates the new table, copies the shared columns and swaps it in. A new
+// NOT NULL column needs a default to fill in the copied rows, unless it is
+// the INTEGER PRIMARY KEY that SQLite numbers itself.
+fn rebuild_table(old: &TableSchema, new: &TableSchema) -> Result<Vec<String>, ()> {
+    let rowid = |c: &ColumnSchema| c.primary_key && c.sql_type == "INTEGER" && new.primary_key().len() == 1;
+
+    if new.columns.iter().any(|c| old.column(&c.name).is_none() && !c.nullable && c.default.is_none() && !rowid(c)) {
+        return Err(());
+    }
+
+    let name = new.table().name();
+    let mut parts = name.rsplitn(2, '.');
+    let last = parts.next().unwrap_or_default().to_string();
+    let temp = match parts.next() {
+        Some(schema) => format!("{}._new_{}", schema, last),
+        None => format!("_new_{}", last),
+    };
+
+    let columns = new
+        .columns
+        .iter()
+        .filter(|c| old.column(&c.name).is_some())
+        .map(|c| quote_ident(&c.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let temp_table = Table::new(&temp, None).qualified();
+    let create = TableSchema { name: temp, ..new.clone() };
+
+    Ok(vec![
+        create.create_sql(false),
+        format!("INSERT INTO {} ({}) SELECT {} FROM {}", temp_table, columns, columns, old.table().qualified()),
+        format!("DROP TABLE {}", old.table().qualified()),
+        format!("ALTER TABLE {} RENAME TO {}", temp_table, quote_ident(&last)),
+    ])
+}
@@ -3,8 +3,10 @@ use std::fmt;
 use std::ops::Add;
 use std::rc::Rc;
 
+use crate::dialect::{dialect, quote_ident, Dialect};
 use crate::entity::{Column, Entity, Star};
 use crate::expression::and_;
+use crate::macros::for_each_tuple;
 use crate::query::ToValues;
 
 #[derive(Debug, Clone)]
@@ -23,6 +25,13 @@ pub trait HasOption {}
 
 impl<A> HasOption for Option<A> {}
 
+// Expr a or Expr (Maybe a). Accessors of nullable columns keep the value
+// type, so functions that take a nullable argument accept both.
+pub trait Nullable<A> {}
+
+impl<A> Nullable<A> for A {}
+impl<A> Nullable<A> for Option<A> {}
+
 // Expr (PreprocessedFrom a)
 pub trait HasPreprocess {}
 
@@ -37,23 +46,283 @@ pub trait ToLiteral {
 
 impl ToLiteral for String {
     fn to_literal<'a, A: fmt::Display>(v: &'a A) -> String {
-        format!("'{}'", v)
+        quote_string(&v.to_string())
+    }
+}
+
+impl ToLiteral for &str {
+    fn to_literal<A: fmt::Display>(v: &A) -> String {
+        quote_string(&v.to_string())
+    }
+}
+
+impl ToLiteral for char {
+    fn to_literal<A: fmt::Display>(v: &A) -> String {
+        quote_string(&v.to_string())
+    }
+}
+
+// Quotes a string literal for the current dialect. NUL can't be written
+// inside a literal on SQLite and PostgreSQL, so it is concatenated in.
+// PostgreSQL text can't hold NUL at all and rejects CHR(0) when the
+// statement runs, which beats silently cutting the value short.
+pub fn quote_string(s: &str) -> String {
+    let d = dialect();
+
+    if d == Dialect::Standard && s.contains('\0') {
+        return unicode_string(s);
+    }
+
+    let mut parts: Vec<String> = vec![];
+    let mut quoted = String::with_capacity(s.len() + 2);
+
+    quoted.push('\'');
+    for c in s.chars() {
+        match (d, c) {
+            (_, '\'') => quoted.push_str("''"),
+            (Dialect::Mysql, '\\') => quoted.push_str("\\\\"),
+            (Dialect::Mysql, '\0') => quoted.push_str("\\0"),
+            (_, '\0') => {
+                quoted.push('\'');
+                parts.push(std::mem::replace(&mut quoted, "'".to_string()));
+                parts.push(if d == Dialect::Sqlite { "char(0)" } else { "CHR(0)" }.to_string());
+            }
+            (_, c) => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+
+    if parts.is_empty() {
+        return quoted;
+    }
+
+    parts.push(quoted);
+    format!("({})", parts.join(" || "))
+}
+
+// SQL standard Unicode escape string, `U&'a\0000b'`
+fn unicode_string(s: &str) -> String {
+    let mut quoted = String::from("U&'");
+
+    for c in s.chars() {
+        match c {
+            '\'' => quoted.push_str("''"),
+            '\\' => quoted.push_str("\\\\"),
+            '\0' => quoted.push_str("\\0000"),
+            c => quoted.push(c),
+        }
     }
+    quoted.push('\'');
+
+    quoted
 }
 
 impl ToLiteral for bool {}
+impl ToLiteral for i8 {}
+impl ToLiteral for i16 {}
 impl ToLiteral for i32 {}
+impl ToLiteral for i64 {}
+impl ToLiteral for u8 {}
+impl ToLiteral for u16 {}
 impl ToLiteral for u32 {}
+impl ToLiteral for u64 {}
+impl ToLiteral for f32 {}
+impl ToLiteral for f64 {}
+impl ToLiteral for Vec<u8> {}
+impl<T: ToLiteral> ToLiteral for Option<T> {}
 impl ToLiteral for Column {}
 impl ToLiteral for Star {}
 impl<S> ToLiteral for Alias<S> {}
 
+// Rust value embedded into a query by `val_`
+pub trait SqlLiteral: ToLiteral {
+    fn sql_literal(&self) -> String;
+
+    // Value written by an entity insert, `None` leaves the column to its DEFAULT
+    fn insert_value(&self) -> Option<String> {
+        Some(self.sql_literal())
+    }
+}
+
+macro_rules! display_literal {
+    ($($t:ty),*) => {
+        $(
+            impl SqlLiteral for $t {
+                fn sql_literal(&self) -> String {
+                    Self::to_literal(self)
+                }
+            }
+        )*
+    };
+}
+
+display_literal!(String, char, bool, i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl SqlLiteral for &str {
+    fn sql_literal(&self) -> String {
+        Self::to_literal(self)
+    }
+}
+
+impl<T: SqlLiteral> SqlLiteral for Option<T> {
+    fn sql_literal(&self) -> String {
+        match self {
+            Some(v) => v.sql_literal(),
+            None => "NULL".to_string(),
+        }
+    }
+
+    fn insert_value(&self) -> Option<String> {
+        self.as_ref().map(|v| v.sql_literal())
+    }
+}
+
+macro_rules! float_literal {
+    ($($t:ty => $name:expr),*) => {
+        $(
+            impl SqlLiteral for $t {
+                fn sql_literal(&self) -> String {
+                    if self.is_finite() {
+                        return format!("{:?}", self);
+                    }
+
+                    let special = if self.is_nan() {
+                        "NaN"
+                    } else if self.is_sign_positive() {
+                        "Infinity"
+                    } else {
+                        "-Infinity"
+                    };
+
+                    match dialect() {
+                        // SQLite stores NaN as NULL and parses overflowing reals as infinity
+                        Dialect::Sqlite if self.is_nan() => "NULL".to_string(),
+                        Dialect::Sqlite if self.is_sign_positive() => "9e999".to_string(),
+                        Dialect::Sqlite => "-9e999".to_string(),
+                        // MySQL has no NaN or infinite DOUBLE, not even through CAST, so they
+                        // become NULL: `x < val_(f64::INFINITY)` matches nothing and NOT NULL
+                        // columns reject them. Check `is_finite` before binding one.
+                        Dialect::Mysql => "NULL".to_string(),
+                        Dialect::Postgres => format!("'{}'::{}", special, $name),
+                        Dialect::Standard => format!("CAST('{}' AS {})", special, <$t>::type_name(Dialect::Standard)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_literal!(f32 => "float4", f64 => "float8");
+
+impl SqlLiteral for Vec<u8> {
+    fn sql_literal(&self) -> String {
+        let hex = self.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+
+        match dialect() {
+            Dialect::Postgres => format!("'\\x{}'::bytea", hex),
+            _ => format!("X'{}'", hex),
+        }
+    }
+}
+
+// Type name used by CAST(x AS ..)
+pub trait SqlType {
+    fn type_name(d: Dialect) -> &'static str;
+
+    // Type of a column holding the value, MySQL casts to other names than it declares
+    fn column_type(d: Dialect) -> &'static str {
+        Self::type_name(d)
+    }
+
+    fn nullable() -> bool {
+        false
+    }
+}
+
+macro_rules! sql_type {
+    ($($t:ty => ($standard:expr, $sqlite:expr, $postgres:expr, $mysql:expr $(, $mysql_column:expr)?)),*) => {
+        $(
+            impl SqlType for $t {
+                fn type_name(d: Dialect) -> &'static str {
+                    match d {
+                        Dialect::Standard => $standard,
+                        Dialect::Sqlite => $sqlite,
+                        Dialect::Postgres => $postgres,
+                        Dialect::Mysql => $mysql,
+                    }
+                }
+
+                fn column_type(d: Dialect) -> &'static str {
+                    match d {
+                        Dialect::Mysql => [$($mysql_column,)? $mysql][0],
+                        _ => Self::type_name(d),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+sql_type!(
+    bool => ("BOOLEAN", "INTEGER", "BOOLEAN", "SIGNED", "BOOLEAN"),
+    i8 => ("SMALLINT", "INTEGER", "SMALLINT", "SIGNED", "TINYINT"),
+    i16 => ("SMALLINT", "INTEGER", "SMALLINT", "SIGNED", "SMALLINT"),
+    i32 => ("INTEGER", "INTEGER", "INTEGER", "SIGNED", "INT"),
+    i64 => ("BIGINT", "INTEGER", "BIGINT", "SIGNED", "BIGINT"),
+    u8 => ("SMALLINT", "INTEGER", "SMALLINT", "UNSIGNED", "TINYINT UNSIGNED"),
+    u16 => ("INTEGER", "INTEGER", "INTEGER", "UNSIGNED", "SMALLINT UNSIGNED"),
+    u32 => ("BIGINT", "INTEGER", "BIGINT", "UNSIGNED", "INT UNSIGNED"),
+    u64 => ("NUMERIC(20)", "INTEGER", "NUMERIC(20)", "UNSIGNED", "BIGINT UNSIGNED"),
+    f32 => ("REAL", "REAL", "REAL", "FLOAT"),
+    f64 => ("DOUBLE PRECISION", "REAL", "DOUBLE PRECISION", "DOUBLE"),
+    char => ("CHAR(1)", "TEXT", "CHAR(1)", "CHAR(1)"),
+    String => ("VARCHAR", "TEXT", "TEXT", "CHAR", "VARCHAR(255)"),
+    Vec<u8> => ("VARBINARY", "BLOB", "BYTEA", "BINARY", "BLOB")
+);
+
+impl SqlType for &str {
+    fn type_name(d: Dialect) -> &'static str {
+        String::type_name(d)
+    }
+
+    fn column_type(d: Dialect) -> &'static str {
+        String::column_type(d)
+    }
+}
+
+impl<T: SqlType> SqlType for Option<T> {
+    fn type_name(d: Dialect) -> &'static str {
+        T::type_name(d)
+    }
+
+    fn column_type(d: Dialect) -> &'static str {
+        T::column_type(d)
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod decimal_impl {
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    impl ToLiteral for Decimal {}
+
+    display_literal!(Decimal);
+
+    sql_type!(Decimal => ("NUMERIC", "NUMERIC", "NUMERIC", "DECIMAL(65, 30)"));
+}
+
 #[derive(Clone)]
 pub struct Alias<A>(pub String, pub String, std::marker::PhantomData<A>);
 
 impl<A> fmt::Display for Alias<A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} AS {}", self.0, self.1)
+        write!(f, "{} AS {}", self.0, quote_ident(&self.1))
     }
 }
 
@@ -117,25 +386,71 @@ impl<A> fmt::Display for Raw<A> {
     }
 }
 
-pub struct CompositKey<A>(pub A);
+#[derive(Clone)]
+pub struct Literal<A>(pub String, pub std::marker::PhantomData<A>);
 
-impl<A: fmt::Display + Clone + ToLiteral> HasValue<A> for CompositKey<A> {
+impl<A: ToLiteral> HasValue<A> for Literal<A> {
     type Output = A;
 
-    fn to_sql(&self) -> String
-    where
-        Self: Sized,
-    {
-        Self::Output::to_literal(&self.0)
+    fn to_sql(&self) -> String {
+        self.0.clone()
     }
 }
 
-impl<A: fmt::Display> fmt::Display for CompositKey<A> {
+impl<A> fmt::Display for Literal<A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+// Row value such as `(a, b)`, typed by the tuple of its element types
+pub struct CompositKey<A>(pub Vec<String>, pub std::marker::PhantomData<A>);
+
+impl<A> ToLiteral for CompositKey<A> {}
+
+impl<A> HasValue<A> for CompositKey<A> {
+    type Output = CompositKey<A>;
+
+    fn to_sql(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<A> fmt::Display for CompositKey<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})", self.0.join(", "))
+    }
+}
+
+pub trait RowValue {
+    type Value;
+
+    fn row(&self) -> Vec<String>;
+}
+
+macro_rules! row_value {
+    ($(($t:ident, $o:ident, $v:ident, $i:tt)),+) => {
+        impl<$($t, $o),+> RowValue for ($(Rc<dyn HasValue<$t, Output = $o>>,)+) {
+            type Value = ($($t,)+);
+
+            fn row(&self) -> Vec<String> {
+                vec![$(self.$i.to_sql()),+]
+            }
+        }
+
+        impl<$($t: ToLiteral),+> ToLiteral for ($($t,)+) {}
+
+        impl<$($t: SqlLiteral),+> SqlLiteral for ($($t,)+) {
+            fn sql_literal(&self) -> String {
+                let ($($v,)+) = self;
+                format!("({})", [$($v.sql_literal()),+].join(", "))
+            }
+        }
+    };
+}
+
+for_each_tuple!(row_value);
+
 // Expr (ValueList a)
 pub trait HasValueList<A>: fmt::Display {
     fn is_empty(&self) -> bool;
@@ -146,7 +461,7 @@ pub enum List<A, B: ToLiteral> {
     Empty,
 }
 
-impl<A: fmt::Display, B: ToLiteral> HasValueList<A> for List<A, B> {
+impl<A, B: ToLiteral> HasValueList<A> for List<A, B> {
     fn is_empty(&self) -> bool {
         match self {
             List::NonEmpty(_) => false,
@@ -333,7 +648,7 @@ impl<A, B: ToLiteral> fmt::Display for SetValue<A, B> {
     }
 }
 
-pub type SetClause = Box<dyn HasSet>;
+pub type SetClause = Rc<dyn HasSet>;
 
 // LIMIT / OFFSET
 
@@ -392,7 +707,7 @@ pub struct GroupBy<A, B>(pub SqlExpr<A, B>);
 
 impl<A, B> HasGroupBy for GroupBy<A, B> {}
 
-pub type GroupByClause = Box<dyn HasGroupBy>;
+pub type GroupByClause = Rc<dyn HasGroupBy>;
 
 impl<A, B> fmt::Display for GroupBy<A, B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -456,10 +771,11 @@ impl fmt::Display for Distinct {
 
 pub type DistinctClause = Distinct;
 
-pub type ValuesClause = Box<dyn HasValues>;
+pub type ValuesClause = Rc<dyn HasValues>;
 
-pub type DuplicateClause = Box<dyn HasDuplicateKey>;
+pub type DuplicateClause = Rc<dyn HasDuplicateKey>;
 
+#[derive(Clone)]
 pub struct QueryState {
     pub distinct_clause: DistinctClause,
     pub from_clause: Vec<FromClause>,
@@ -471,6 +787,8 @@ pub struct QueryState {
     pub groupby_clause: Vec<GroupByClause>,
     pub having_clause: WhereClause,
     pub duplicate_clause: Vec<DuplicateClause>,
+    pub conflict_clause: Option<ConflictClause>,
+    pub returning_clause: Option<String>,
 }
 
 impl Default for QueryState {
@@ -486,20 +804,74 @@ impl Default for QueryState {
             groupby_clause: vec![],
             having_clause: WhereClause::No,
             duplicate_clause: vec![],
+            conflict_clause: None,
+            returning_clause: None,
         }
     }
 }
 
+// Rendered FROM items, to tell whether two queries read the same tables
+fn from_items(clause: &[FromClause]) -> Vec<String> {
+    clause
+        .iter()
+        .map(|f| match f {
+            FromClause::OnClause(on) => on.to_sql(),
+            _ => f.to_string(),
+        })
+        .collect()
+}
+
+// List clauses are concatenated and WHERE / HAVING are ANDed. Of the single
+// valued clauses (LIMIT / OFFSET, VALUES, ON CONFLICT, RETURNING) the
+// right-hand side wins where it is set. A FROM read by both sides is kept once.
 impl Add for QueryState {
     type Output = Self;
 
-    fn add(mut self, other: Self) -> Self::Output {
-        self.from_clause.append(&mut other.from_clause.clone());
-        self.where_clause = other.where_clause;
-        self
+    fn add(self, other: Self) -> Self::Output {
+        let mut from_clause = self.from_clause;
+        if from_items(&from_clause) != from_items(&other.from_clause) {
+            from_clause.extend(other.from_clause);
+        }
+
+        let distinct_clause = match (self.distinct_clause, other.distinct_clause) {
+            (Distinct::On(mut a), Distinct::On(mut b)) => {
+                a.append(&mut b);
+                Distinct::On(a)
+            }
+            (a @ Distinct::On(_), _) | (_, a @ Distinct::On(_)) => a,
+            (Distinct::All, b) => b,
+            (a, _) => a,
+        };
+
+        let mut order_clause = self.order_clause;
+        order_clause.extend(other.order_clause);
+        let mut set_clause = self.set_clause;
+        set_clause.extend(other.set_clause);
+        let mut groupby_clause = self.groupby_clause;
+        groupby_clause.extend(other.groupby_clause);
+        let mut duplicate_clause = self.duplicate_clause;
+        duplicate_clause.extend(other.duplicate_clause);
+
+        QueryState {
+            distinct_clause,
+            from_clause,
+            where_clause: self.where_clause.add(other.where_clause),
+            order_clause,
+            set_clause,
+            values_clause: other.values_clause.or(self.values_clause),
+            limit_clause: other.limit_clause + self.limit_clause,
+            groupby_clause,
+            having_clause: self.having_clause.add(other.having_clause),
+            duplicate_clause,
+            conflict_clause: other.conflict_clause.or(self.conflict_clause),
+            returning_clause: other.returning_clause.or(self.returning_clause),
+        }
     }
 }
 
+// Column names and the rows filling them
+pub type ValuesGroup = (Vec<String>, Vec<Vec<String>>);
+
 pub trait HasValues {
     fn columns(&self) -> Vec<String> {
         vec![]
@@ -507,6 +879,10 @@ pub trait HasValues {
     fn values(&self) -> Vec<Vec<String>> {
         vec![]
     }
+    // Rows inserted by separate statements, each with its own columns
+    fn groups(&self) -> Vec<ValuesGroup> {
+        vec![(self.columns(), self.values())]
+    }
 }
 
 pub struct Values<A: ToValues, B: ToValues>(pub A, pub Vec<B>);
@@ -521,6 +897,15 @@ impl<A: ToValues, B: ToValues> HasValues for Values<A, B> {
     }
 }
 
+// Rows already rendered to SQL, as produced by entity inserts
+pub struct RawValues(pub Vec<ValuesGroup>);
+
+impl HasValues for RawValues {
+    fn groups(&self) -> Vec<ValuesGroup> {
+        self.0.clone()
+    }
+}
+
 pub trait HasDuplicateKey {
     fn dup_keys(&self) -> (String, String);
 }
@@ -532,3 +917,119 @@ impl<A, B> HasDuplicateKey for DuplicateKey<A, B> {
         (self.0.to_sql(), self.1.to_sql())
     }
 }
+
+// ON CONFLICT
+
+#[derive(Clone)]
+pub enum ConflictTarget {
+    Columns(String),
+    Constraint(String),
+}
+
+#[derive(Clone)]
+pub enum ConflictAction {
+    Nothing,
+    Update(Vec<Rc<dyn HasSet>>, WhereClause),
+}
+
+#[derive(Clone)]
+pub struct ConflictClause(pub ConflictTarget, pub ConflictAction);
+
+impl fmt::Display for ConflictClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if dialect() == Dialect::Mysql {
+            // MySQL has no conflict target, DO NOTHING is rendered as INSERT IGNORE instead.
+            return match &self.1 {
+                ConflictAction::Nothing => Ok(()),
+                ConflictAction::Update(sets, cond) => {
+                    let sets = sets
+                        .iter()
+                        .map(|s| match cond {
+                            WhereClause::No => format!("{} = {}", s.column(), s.value()),
+                            _ => format!("{} = IF({}, {}, {})", s.column(), cond, s.value(), s.column()),
+                        })
+                        .collect::<Vec<_>>();
+                    write!(f, "ON DUPLICATE KEY UPDATE {}", sets.join(", "))
+                }
+            };
+        }
+
+        match &self.0 {
+            ConflictTarget::Columns(c) => write!(f, "ON CONFLICT ({})", c)?,
+            ConflictTarget::Constraint(c) => write!(f, "ON CONFLICT ON CONSTRAINT {}", quote_ident(c))?,
+        }
+
+        match &self.1 {
+            ConflictAction::Nothing => write!(f, " DO NOTHING"),
+            ConflictAction::Update(sets, cond) => {
+                let sets = sets.iter().map(|s| format!("{} = {}", s.column(), s.value())).collect::<Vec<_>>();
+                write!(f, " DO UPDATE SET {}", sets.join(", "))?;
+
+                match cond {
+                    WhereClause::No => Ok(()),
+                    _ => write!(f, " WHERE {}", cond),
+                }
+            }
+        }
+    }
+}
+
+// TRUNCATE / DROP options
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Identity {
+    Restart,
+    Continue,
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identity::Restart => write!(f, "RESTART IDENTITY"),
+            Identity::Continue => write!(f, "CONTINUE IDENTITY"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropBehavior {
+    Cascade,
+    Restrict,
+}
+
+impl fmt::Display for DropBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DropBehavior::Cascade => write!(f, "CASCADE"),
+            DropBehavior::Restrict => write!(f, "RESTRICT"),
+        }
+    }
+}
+
+// FOREIGN KEY ... ON DELETE / ON UPDATE
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+        }
+    }
+}
+
+// Table constraint declared in CREATE TABLE
+pub trait HasConstraint: fmt::Display {}
+
+pub type ConstraintClause = Rc<dyn HasConstraint>;
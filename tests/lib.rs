@@ -1,8 +1,7 @@
 extern crate hone;
 
-#[cfg_attr(test, macro_use)]
+#[macro_use]
 extern crate diesel;
-extern crate diesel_migrations;
 
 mod orm;
 mod query;
@@ -1 +0,0 @@
-DROP TABLE downloads;
\ No newline at end of file
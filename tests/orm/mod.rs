@@ -1,18 +1,21 @@
-use std::fs::File;
-use std::path::Path;
+// diesel 1.4's table! and derives put their impls inside functions
+#![allow(non_local_definitions)]
+
 use std::rc::Rc;
 
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
 use diesel::sql_query;
-use diesel_migrations;
 
-use hone::entity::{Column, HasEntityDef, Table as Tbl};
+use hone::dialect::*;
+use hone::entity::{Column, ColumnDef, HasEntityDef, HasTableDef, Insertable, Table};
 use hone::expression::*;
+use hone::{hone_entity, hone_insertable, hone_model, hone_table};
 use hone::query::*;
 use hone::types::*;
 
 fn establish_connection() -> SqliteConnection {
-    let database_url = "/tmp/hoge.db";
+    let database_url = ":memory:";
 
     SqliteConnection::establish(&database_url).unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
@@ -31,45 +34,27 @@ struct Download {
     pub version: String,
 }
 
-impl Download {
-    pub fn id(&self) -> Rc<HasValue<i32, Output = Column>> {
-        never_("downloads.id")
-    }
-
-    pub fn version(&self) -> Rc<HasValue<String, Output = Column>> {
-        never_("downloads.version")
-    }
-}
-
-impl HasEntityDef for Download {
-    fn table_name() -> Tbl {
-        Tbl::new("downloads", None)
-    }
-
-    fn columns() -> Vec<&'static str> {
-        vec!["id", "version"]
-    }
-}
+hone_model!(Download, downloads, id => i32, version => String);
+hone_entity!(Download, downloads, id, version);
+hone_table!(Download, id [primary_key], version);
 
-impl HasQuery for Download {
-    type T = Download;
-}
+hone_insertable!(Download, id, version);
 
-fn setup() {
-    let connection = establish_connection();
-    let path = Path::new("tests/migrations");
-    let mut output = File::create("/tmp/output.txt").unwrap();
+fn setup(connection: &SqliteConnection) {
+    let rows = (1..=3).map(|i| Download { id: i, version: format!("0.{}", i) }).collect::<Vec<_>>();
 
-    let _ = diesel_migrations::run_pending_migrations_in_directory(&connection, path, &mut output);
+    with_dialect(Dialect::Sqlite, || {
+        connection.batch_execute(&create_table::<Download>().if_not_exists_().to_sql()).unwrap();
+        connection.batch_execute(&bulk_insert_values(&rows).unwrap().to_sql()).unwrap();
+    });
 }
 
 #[test]
 fn test_diesel() {
     use super::orm::downloads::dsl::*;
 
-    setup();
-
     let connection = establish_connection();
+    setup(&connection);
 
     let a = downloads.filter(id.eq(1)).load::<Download>(&connection).unwrap();
     let a = a.first().unwrap();
@@ -86,3 +71,75 @@ fn test_diesel() {
 
     assert_eq!(a.version, b.version);
 }
+
+// hone_diesel! needs the crate's diesel feature
+#[cfg(feature = "diesel")]
+mod schema {
+    use super::*;
+    use hone::hone_diesel;
+
+    #[derive(Default)]
+    struct DownloadRow;
+
+    hone_diesel!(DownloadRow, downloads, id, version);
+
+    table! {
+        releases (release_id) {
+            release_id -> BigInt,
+            #[sql_name = "type"]
+            kind -> Nullable<Text>,
+        }
+    }
+
+    #[derive(Default)]
+    struct Release;
+
+    hone_diesel!(Release, releases, release_id, kind);
+
+    #[test]
+    fn test_hone_diesel() {
+        let a = Query::<Release>::from_by(|q, r| {
+            let q = q.where_(is_null_(r.kind()));
+            let q = q.where_(eq_(r.release_id(), val_(1i64)));
+            q.return_(r.kind())
+        });
+
+        assert_eq!(
+            select(a.unwrap()).to_sql(),
+            "SELECT releases.type FROM releases WHERE ((releases.type IS NULL) AND (releases.release_id = 1))".to_string()
+        );
+        assert_eq!(Release::columns(), vec!["release_id", "type"]);
+        assert_eq!(
+            coalesce_(Release.kind(), val_("stable".to_string())).to_string(),
+            "COALESCE(releases.type, 'stable')".to_string()
+        );
+        assert_eq!(
+            with_dialect(Dialect::Postgres, || create_table::<Release>().to_sql()),
+            "CREATE TABLE releases (release_id BIGINT NOT NULL PRIMARY KEY, type TEXT)".to_string()
+        );
+        assert_eq!(
+            with_dialect(Dialect::Sqlite, || create_table::<DownloadRow>().to_sql()),
+            "CREATE TABLE downloads (id INTEGER NOT NULL PRIMARY KEY, version TEXT NOT NULL)".to_string()
+        );
+    }
+
+    table! {
+        memberships (user_id, library_id) {
+            user_id -> Integer,
+            library_id -> Integer,
+        }
+    }
+
+    #[derive(Default)]
+    struct Membership;
+
+    hone_diesel!(Membership, memberships, user_id, library_id);
+
+    #[test]
+    fn test_hone_diesel_composite_key() {
+        assert_eq!(
+            create_table::<Membership>().to_sql(),
+            "CREATE TABLE memberships (user_id INTEGER NOT NULL, library_id INTEGER NOT NULL, PRIMARY KEY (user_id, library_id))".to_string()
+        );
+    }
+}
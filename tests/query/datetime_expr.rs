@@ -0,0 +1,121 @@
+use std::rc::Rc;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use hone::datetime::DatePart;
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::types::*;
+
+fn created_at() -> Rc<dyn HasValue<NaiveDateTime, Output = Column>> {
+    Rc::new(Column::new("User.created_at"))
+}
+
+#[test]
+fn test_datetime_literal() {
+    let date = NaiveDate::from_ymd_opt(2019, 1, 2).unwrap();
+    let datetime = date.and_hms_micro_opt(3, 4, 5, 600).unwrap();
+    let utc = Utc.from_utc_datetime(&datetime);
+
+    assert_eq!("DATE '2019-01-02'", val_(date).to_sql());
+    assert_eq!("TIMESTAMP '2019-01-02 03:04:05.0006'", val_(datetime).to_sql());
+
+    with_dialect(Dialect::Sqlite, || {
+        assert_eq!("'2019-01-02'", val_(date).to_sql());
+        assert_eq!("'2019-01-02 03:04:05.0006+00:00'", val_(utc).to_sql());
+        assert_eq!("'90 seconds'", val_(Duration::seconds(90)).to_sql());
+    });
+
+    with_dialect(Dialect::Postgres, || {
+        assert_eq!("TIMESTAMPTZ '2019-01-02 03:04:05.0006+00:00'", val_(utc).to_sql());
+        assert_eq!("INTERVAL '-1.5 seconds'", val_(Duration::milliseconds(-1500)).to_sql());
+    });
+
+    with_dialect(Dialect::Mysql, || {
+        assert_eq!("TIMESTAMP '2019-01-02 03:04:05.0006'", val_(utc).to_sql());
+        assert_eq!("INTERVAL 90 SECOND", val_(Duration::seconds(90)).to_sql());
+    });
+}
+
+#[test]
+fn test_datetime_functions() {
+    let render = |d| {
+        with_dialect(d, || {
+            let now: Rc<dyn HasValue<NaiveDateTime, Output = Column>> = now_();
+            let day = date_trunc_(DatePart::Day, created_at());
+            let year = extract_(DatePart::Year, created_at());
+            let later = date_add_(created_at(), val_(Duration::days(1)));
+
+            vec![now.to_sql(), day.to_sql(), year.to_sql(), later.to_sql()]
+        })
+    };
+
+    assert_eq!(
+        render(Dialect::Sqlite),
+        vec![
+            "datetime('now')",
+            "strftime('%Y-%m-%d 00:00:00', User.created_at)",
+            "CAST(strftime('%Y', User.created_at) AS INTEGER)",
+            "datetime(User.created_at, '86400 seconds')",
+        ]
+    );
+
+    assert_eq!(
+        render(Dialect::Postgres),
+        vec![
+            "NOW()",
+            "date_trunc('day', User.created_at)",
+            "EXTRACT(YEAR FROM User.created_at)",
+            "(User.created_at + INTERVAL '86400 seconds')",
+        ]
+    );
+
+    assert_eq!(
+        render(Dialect::Mysql),
+        vec![
+            "NOW()",
+            "CAST(DATE_FORMAT(User.created_at, '%Y-%m-%d 00:00:00') AS DATETIME)",
+            "EXTRACT(YEAR FROM User.created_at)",
+            "(User.created_at + INTERVAL 86400 SECOND)",
+        ]
+    );
+}
+
+#[test]
+fn test_now() {
+    let render = |d| {
+        with_dialect(d, || {
+            let date: Rc<dyn HasValue<NaiveDate, Output = Column>> = now_();
+            let time: Rc<dyn HasValue<NaiveTime, Output = Column>> = now_();
+
+            (date.to_sql(), time.to_sql(), current_date_::<NaiveDate>().to_sql())
+        })
+    };
+
+    assert_eq!(render(Dialect::Sqlite), ("date('now')".to_string(), "time('now')".to_string(), "date('now')".to_string()));
+    assert_eq!(render(Dialect::Postgres), ("CURRENT_DATE".to_string(), "CURRENT_TIME".to_string(), "CURRENT_DATE".to_string()));
+    assert_eq!(render(Dialect::Standard), ("CURRENT_DATE".to_string(), "CURRENT_TIME".to_string(), "CURRENT_DATE".to_string()));
+}
+
+#[test]
+fn test_datetime_sqlite() {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    let (later, earlier) = with_dialect(Dialect::Sqlite, || {
+        let start = val_(NaiveDate::from_ymd_opt(2019, 1, 31).unwrap().and_hms_opt(23, 0, 0).unwrap());
+        let later = date_add_(start.clone(), val_(Duration::hours(2)));
+        let earlier = date_sub_(start, val_(Duration::minutes(30)));
+
+        (later.to_sql(), earlier.to_sql())
+    });
+
+    let later = diesel::select(sql::<Text>(&later)).get_result::<String>(&connection).unwrap();
+    let earlier = diesel::select(sql::<Text>(&earlier)).get_result::<String>(&connection).unwrap();
+
+    assert_eq!(later, "2019-02-01 01:00:00");
+    assert_eq!(earlier, "2019-01-31 22:30:00");
+}
@@ -0,0 +1,205 @@
+use std::rc::Rc;
+
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::query::*;
+use hone::types::*;
+use hone::{hone_entity, hone_model};
+
+use crate::query::model::*;
+
+#[test]
+fn test_create_table() {
+    assert_eq!(
+        create_table::<Account>().to_sql(),
+        "CREATE TABLE Account (account_id BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \
+         name VARCHAR NOT NULL DEFAULT '' UNIQUE, nickname VARCHAR)"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || create_table::<Account>().if_not_exists_().to_sql()),
+        "CREATE TABLE IF NOT EXISTS Account (account_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, \
+         name TEXT NOT NULL DEFAULT '' UNIQUE, nickname TEXT)"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || create_table::<Account>().to_sql()),
+        "CREATE TABLE Account (account_id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY, \
+         name VARCHAR(255) NOT NULL DEFAULT '' UNIQUE, nickname VARCHAR(255))"
+            .to_string()
+    );
+}
+
+#[derive(Default)]
+struct Membership;
+
+hone_model!(Membership, Membership, user_id => i32, library_id => i32, admin => bool);
+hone_entity!(Membership, Membership, user_id, library_id, admin);
+
+impl HasTableDef for Membership {
+    fn column_defs() -> Vec<ColumnDef> {
+        vec![
+            ColumnDef::new::<i32>("user_id").primary_key(),
+            ColumnDef::new::<i32>("library_id").primary_key(),
+            ColumnDef::new::<bool>("admin").default(false),
+        ]
+    }
+}
+
+#[test]
+fn test_create_table_composite_key() {
+    assert_eq!(
+        with_dialect(Dialect::Postgres, || create_table::<Membership>().to_sql()),
+        "CREATE TABLE Membership (user_id INTEGER NOT NULL, library_id INTEGER NOT NULL, admin BOOLEAN NOT NULL DEFAULT false, \
+         PRIMARY KEY (user_id, library_id))"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_drop_table() {
+    assert_eq!(drop_table::<Account>().to_sql(), "DROP TABLE Account".to_string());
+    assert_eq!(drop_table::<Account>().if_exists_().cascade_().to_sql(), "DROP TABLE IF EXISTS Account CASCADE".to_string());
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || drop_table::<Account>().if_exists_().cascade_().to_sql()),
+        "DROP TABLE IF EXISTS Account".to_string()
+    );
+}
+
+#[test]
+fn test_create_table_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+
+    with_dialect(Dialect::Sqlite, || {
+        connection.batch_execute(&create_table::<Account>().to_sql()).unwrap();
+        connection.batch_execute(&create_table::<Account>().if_not_exists_().to_sql()).unwrap();
+
+        let rows = [
+            Account { name: "a".to_string(), ..Account::default() },
+            Account { name: "b".to_string(), nickname: Some("bb".to_string()), ..Account::default() },
+        ];
+        connection.batch_execute(&insert_into_values(&rows[0]).to_sql()).unwrap();
+        connection.batch_execute(&insert_into_values(&rows[1]).to_sql()).unwrap();
+        assert!(connection.batch_execute(&insert_into_values(&rows[0]).to_sql()).is_err());
+
+        let rows = sql::<Text>("(SELECT group_concat(account_id || name || coalesce(nickname, '-')) FROM Account)");
+        assert_eq!("1a-,2bbb", diesel::select(rows).get_result::<String>(&connection).unwrap());
+
+        connection.batch_execute(&drop_table::<Account>().to_sql()).unwrap();
+        connection.batch_execute(&drop_table::<Account>().if_exists_().to_sql()).unwrap();
+    });
+}
+
+#[test]
+fn test_create_index() {
+    let index = || create_index_::<User, _>("user_email", |u| (u.email(), desc_(u.user_id()))).unique_();
+
+    assert_eq!(index().to_sql(), "CREATE UNIQUE INDEX user_email ON User (email, user_id DESC)".to_string());
+
+    assert_eq!(
+        index().if_not_exists_().where_(|u: User| is_not_null_(u.email())).unwrap().to_sql(),
+        "CREATE UNIQUE INDEX IF NOT EXISTS user_email ON User (email, user_id DESC) WHERE (email IS NOT NULL)".to_string()
+    );
+
+    with_dialect(Dialect::Mysql, || {
+        assert!(index().where_(|u: User| is_not_null_(u.email())).is_err());
+        assert_eq!(
+            index().if_not_exists_().to_sql(),
+            "CREATE UNIQUE INDEX user_email ON User (email, user_id DESC)".to_string()
+        );
+    });
+
+    let index = create_index_::<User, _>("user_email_nullif", |u| nullif_(u.email(), val_(String::new())));
+    assert_eq!(index.to_sql(), "CREATE INDEX user_email_nullif ON User ((NULLIF(email, '')))".to_string());
+}
+
+#[test]
+fn test_drop_index() {
+    assert_eq!(drop_index_::<User>("user_email").if_exists_().to_sql(), "DROP INDEX IF EXISTS user_email".to_string());
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || drop_index_::<User>("user_email").to_sql()),
+        "DROP INDEX user_email ON User".to_string()
+    );
+}
+
+fn create_membership() -> String {
+    let fk = foreign_key_(|m: Membership, l: Library| (m.library_id(), l.library_id())).on_delete_(ReferentialAction::Cascade);
+    let check = check_(|m: Membership| not_eq_(m.user_id(), m.library_id())).name_("other_ids");
+
+    create_table::<Membership>().constraint_(fk).constraint_(check).to_sql()
+}
+
+#[test]
+fn test_constraints() {
+    assert_eq!(
+        create_membership(),
+        "CREATE TABLE Membership (user_id INTEGER NOT NULL, library_id INTEGER NOT NULL, admin BOOLEAN NOT NULL DEFAULT false, \
+         PRIMARY KEY (user_id, library_id), FOREIGN KEY (library_id) REFERENCES Library (library_id) ON DELETE CASCADE, \
+         CONSTRAINT other_ids CHECK (user_id <> library_id))"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_unqualified_columns() {
+    let check = check_(|a: Account| not_eq_(a.name(), val_("Account.x".to_string())));
+    assert_eq!(
+        create_table::<Account>().constraint_(check).to_sql(),
+        "CREATE TABLE Account (account_id BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \
+         name VARCHAR NOT NULL DEFAULT '' UNIQUE, nickname VARCHAR, CHECK (name <> 'Account.x'))"
+            .to_string()
+    );
+
+    let index = create_index_::<User, _>("user_email", |u| u.email());
+    let index = index.where_(|u: User| not_eq_(u.email(), val_("AppUser.email".to_string()))).unwrap();
+    assert_eq!(
+        index.to_sql(),
+        "CREATE INDEX user_email ON User (email) WHERE (email <> 'AppUser.email')".to_string()
+    );
+
+    // only while the DDL is built
+    assert_eq!(User::default().email().to_sql(), "User.email".to_string());
+}
+
+#[test]
+fn test_constraints_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::BigInt;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+
+    with_dialect(Dialect::Sqlite, || {
+        let index = create_index_::<Membership, _>("membership_admin", |m| (m.library_id(), desc_(m.admin())));
+        let index = index.where_(|m: Membership| eq_(m.admin(), val_(true))).unwrap();
+
+        connection.batch_execute("PRAGMA foreign_keys = ON").unwrap();
+        connection.batch_execute(&create_table::<Library>().to_sql()).unwrap();
+        connection.batch_execute(&create_membership()).unwrap();
+        connection.batch_execute(&index.to_sql()).unwrap();
+        connection
+            .batch_execute(
+                "INSERT INTO Library VALUES (1, 'x'), (2, 'y');
+                 INSERT INTO Membership VALUES (10, 1, 1), (10, 2, 0);",
+            )
+            .unwrap();
+
+        assert!(connection.batch_execute("INSERT INTO Membership VALUES (3, 3, 0)").is_err());
+        assert!(connection.batch_execute("INSERT INTO Membership VALUES (10, 9, 0)").is_err());
+
+        connection.batch_execute("DELETE FROM Library WHERE library_id = 1").unwrap();
+        let rows = sql::<BigInt>("(SELECT count(*) FROM Membership)");
+        assert_eq!(1, diesel::select(rows).get_result::<i64>(&connection).unwrap());
+
+        connection.batch_execute(&drop_index_::<Membership>("membership_admin").to_sql()).unwrap();
+    });
+}
@@ -1,5 +1,8 @@
+use hone::dialect::*;
+use hone::entity::{HasEntityDef, Table};
 use hone::expression::*;
 use hone::query::*;
+use hone::types::*;
 
 use crate::query::model::*;
 
@@ -19,5 +22,204 @@ fn test_delete() {
 #[test]
 fn test_truncate() {
     let a = Query::<User>::from_();
-    assert_eq!(truncate(a.unwrap()).to_sql(), "TRUNCATE TABLE User".to_string());
+    assert_eq!(truncate(a.unwrap()).unwrap().statements(), vec!["TRUNCATE TABLE User"]);
+}
+
+#[test]
+fn test_truncate_options() {
+    let truncate_all = || {
+        let a = Query::<(User, Library)>::from_();
+        truncate(a.unwrap()).unwrap().restart_identity_().cascade_().map(|t| t.statements())
+    };
+
+    assert_eq!(truncate_all(), Ok(vec!["TRUNCATE TABLE User, Library RESTART IDENTITY CASCADE".to_string()]));
+    assert_eq!(
+        with_dialect(Dialect::Postgres, truncate_all),
+        Ok(vec!["TRUNCATE TABLE User, Library RESTART IDENTITY CASCADE".to_string()])
+    );
+    assert_eq!(with_dialect(Dialect::Mysql, truncate_all), Err(()));
+    assert_eq!(with_dialect(Dialect::Sqlite, truncate_all), Err(()));
+
+    let restart_all = || {
+        let a = Query::<(User, Library)>::from_();
+        truncate(a.unwrap()).unwrap().restart_identity_().restrict_().statements()
+    };
+
+    assert_eq!(with_dialect(Dialect::Mysql, restart_all), vec!["TRUNCATE TABLE User", "TRUNCATE TABLE Library"]);
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, restart_all),
+        vec!["DELETE FROM User", "DELETE FROM Library", "DELETE FROM sqlite_sequence WHERE name IN ('User', 'Library')"]
+    );
+
+    let a = Query::<(User, Library, Account)>::from_();
+    assert_eq!(truncate(a.unwrap()).unwrap().statements(), vec!["TRUNCATE TABLE User, Library, Account"]);
+
+    let a = Query::<User>::from_();
+    assert_eq!(
+        truncate(a.unwrap()).unwrap().continue_identity_().restrict_().statements(),
+        vec!["TRUNCATE TABLE User CONTINUE IDENTITY RESTRICT"]
+    );
+
+    let a = Query::<User>::from_();
+    assert_eq!(with_dialect(Dialect::Sqlite, || truncate(a.unwrap()).unwrap().statements()), vec!["DELETE FROM User"]);
+}
+
+#[derive(Debug, Default, Clone)]
+struct Quoted {}
+
+impl HasEntityDef for Quoted {
+    fn table_name() -> Table {
+        Table::new("it's", None)
+    }
+
+    fn columns() -> Vec<&'static str> {
+        vec![]
+    }
+}
+
+impl HasQuery for Quoted {
+    type T = Quoted;
+}
+
+#[test]
+fn test_truncate_names() {
+    let restart = || truncate(Query::<Quoted>::from_().unwrap()).unwrap().restart_identity_().statements();
+
+    assert_eq!(
+        with_quote_policy(QuotePolicy::WhenNeeded, || with_dialect(Dialect::Sqlite, restart)),
+        vec![r#"DELETE FROM "it's""#, "DELETE FROM sqlite_sequence WHERE name IN ('it''s')"]
+    );
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, restart),
+        vec!["DELETE FROM it's", "DELETE FROM sqlite_sequence WHERE name IN ('it''s')"]
+    );
+
+    // nothing to truncate
+    assert!(truncate(Query::new(User::default())).is_err());
+}
+
+#[test]
+fn test_truncate_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::BigInt;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE User (email TEXT, user_id INTEGER PRIMARY KEY AUTOINCREMENT);
+             CREATE TABLE Library (library_id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT);
+             INSERT INTO User (email) VALUES ('a'), ('b');
+             INSERT INTO Library (title) VALUES ('x');",
+        )
+        .unwrap();
+
+    let statements = with_dialect(Dialect::Sqlite, || truncate(Query::<(User, Library)>::from_().unwrap()).unwrap().restart_identity_().statements());
+    for s in &statements {
+        connection.execute(s).unwrap();
+    }
+    connection.batch_execute("INSERT INTO User (email) VALUES ('c');").unwrap();
+
+    let rows = sql::<BigInt>("(SELECT count(*) FROM Library)");
+    assert_eq!(0, diesel::select(rows).get_result::<i64>(&connection).unwrap());
+
+    let id = sql::<BigInt>("(SELECT user_id FROM User)");
+    assert_eq!(1, diesel::select(id).get_result::<i64>(&connection).unwrap());
+}
+
+#[test]
+fn test_delete_returning() {
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.where_(eq_(a.user_id(), val_(1)));
+        q.returning_((a.user_id(), a.email())).unwrap()
+    });
+
+    assert_eq!(
+        delete(a.unwrap()).to_sql(),
+        "DELETE FROM User WHERE (User.user_id = 1) RETURNING User.user_id, User.email".to_string()
+    );
+
+    with_dialect(Dialect::Mysql, || {
+        let a = Query::<User>::from_by(|q, a| {
+            assert!(q.returning_(a.user_id()).is_err());
+            Query::new(a)
+        });
+        assert!(a.is_ok());
+    });
+}
+
+fn delete_user_by_library() -> String {
+    let a = Query::<InnerJoin<_, _>>::from_by(|q, InnerJoin(a, b): InnerJoin<User, Library>| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        q.where_(eq_(b.title(), val_("x".to_string())))
+    });
+
+    delete_using::<User, _>(a.unwrap()).unwrap().to_sql()
+}
+
+#[test]
+fn test_delete_using() {
+    assert_eq!(
+        with_dialect(Dialect::Postgres, delete_user_by_library),
+        "DELETE FROM User USING Library WHERE (User.user_id = Library.library_id) AND (Library.title = 'x')".to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Mysql, delete_user_by_library),
+        "DELETE User FROM User INNER JOIN Library ON (User.user_id = Library.library_id) WHERE (Library.title = 'x')".to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, delete_user_by_library),
+        "DELETE FROM User WHERE EXISTS (SELECT 1 FROM Library \
+         WHERE (User.user_id = Library.library_id) AND (Library.title = 'x'))"
+            .to_string()
+    );
+
+    let a = Query::<(_, _)>::from_by(|q, (a, b): (User, Library)| {
+        let q = q.where_(eq_(a.user_id(), b.library_id()));
+        q.return_(a)
+    });
+
+    assert_eq!(
+        with_dialect(Dialect::Postgres, || delete_using::<Library, _>(a.unwrap()).unwrap().to_sql()),
+        "DELETE FROM Library USING User WHERE (User.user_id = Library.library_id)".to_string()
+    );
+}
+
+#[test]
+fn test_delete_using_outer_join() {
+    let a = Query::<LeftJoin<_, _>>::from_by(|q, LeftJoin(a, b): LeftJoin<User, Library>| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        q.where_(eq_(a.email(), val_("x".to_string())))
+    });
+    assert!(delete_using::<User, _>(a.unwrap()).is_err());
+
+    // an ON without a join to attach to
+    let a = Query::<(_, _)>::from_by(|q, (a, b): (User, Library)| q.on_(eq_(a.user_id(), b.library_id())));
+    assert!(delete_using::<User, _>(a.unwrap()).is_err());
+}
+
+#[test]
+fn test_delete_using_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE User (email TEXT, user_id INTEGER);
+             CREATE TABLE Library (library_id INTEGER, title TEXT);
+             INSERT INTO User VALUES ('a', 1), ('b', 2), ('c', 3);
+             INSERT INTO Library VALUES (1, 'x'), (2, 'y'), (3, 'x');",
+        )
+        .unwrap();
+
+    connection.batch_execute(&with_dialect(Dialect::Sqlite, delete_user_by_library)).unwrap();
+
+    let rows = sql::<Text>("(SELECT group_concat(email) FROM User)");
+    assert_eq!("b", diesel::select(rows).get_result::<String>(&connection).unwrap());
 }
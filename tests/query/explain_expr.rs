@@ -0,0 +1,95 @@
+use hone::dialect::*;
+use hone::expression::*;
+use hone::plan::*;
+use hone::query::*;
+
+use crate::query::model::*;
+
+fn user_by_email() -> impl ToSql {
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.where_(eq_(a.email(), val_("a".to_string())));
+        q.return_(a.user_id())
+    });
+
+    select(a.unwrap())
+}
+
+#[test]
+fn test_explain() {
+    assert_eq!(
+        explain_(user_by_email()).to_sql(),
+        "EXPLAIN SELECT User.user_id FROM User WHERE (User.email = 'a')".to_string()
+    );
+    assert_eq!(
+        explain_(user_by_email()).analyze_().unwrap().verbose_().format_json_().unwrap().to_sql(),
+        "EXPLAIN (ANALYZE, VERBOSE, FORMAT JSON) SELECT User.user_id FROM User WHERE (User.email = 'a')".to_string()
+    );
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || explain_(user_by_email()).analyze_().unwrap().verbose_().to_sql()),
+        "EXPLAIN ANALYZE SELECT User.user_id FROM User WHERE (User.email = 'a')".to_string()
+    );
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || explain_(user_by_email()).format_json_().unwrap().to_sql()),
+        "EXPLAIN FORMAT=JSON SELECT User.user_id FROM User WHERE (User.email = 'a')".to_string()
+    );
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || explain_(user_by_email()).analyze_().unwrap().to_sql()),
+        "EXPLAIN QUERY PLAN SELECT User.user_id FROM User WHERE (User.email = 'a')".to_string()
+    );
+}
+
+#[test]
+fn test_explain_analyze_json_mysql() {
+    with_dialect(Dialect::Mysql, || {
+        assert!(explain_(user_by_email()).analyze_().unwrap().format_json_().is_err());
+        assert!(explain_(user_by_email()).format_json_().unwrap().analyze_().is_err());
+    });
+}
+
+#[test]
+fn test_query_plan() {
+    let plan = QueryPlan::from_rows(vec![
+        (2, 0, "SEARCH User USING INDEX user_email (email=?)".to_string()),
+        (5, 0, "CORRELATED SCALAR SUBQUERY 1".to_string()),
+        (9, 5, "SCAN TABLE Library".to_string()),
+        (12, 5, "USE TEMP B-TREE FOR ORDER BY".to_string()),
+    ]);
+
+    assert_eq!(plan.nodes.len(), 2);
+    assert_eq!(plan.iter().map(|n| n.id).collect::<Vec<_>>(), vec![2, 5, 9, 12]);
+    assert!(plan.uses_index("user_email"));
+    assert!(!plan.uses_index("user"));
+    assert!(plan.scans("Library"));
+    assert!(!plan.scans("User"));
+    assert_eq!(
+        plan.to_string(),
+        "QUERY PLAN\n\
+         |--SEARCH User USING INDEX user_email (email=?)\n\
+         `--CORRELATED SCALAR SUBQUERY 1\n   \
+         |--SCAN TABLE Library\n   \
+         `--USE TEMP B-TREE FOR ORDER BY\n"
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_query_plan_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::prelude::*;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection.batch_execute("CREATE TABLE User (email TEXT, user_id INTEGER)").unwrap();
+
+    // built under the default dialect
+    assert!(explain_(user_by_email()).query_plan(&connection).is_err());
+
+    let plan = with_dialect(Dialect::Sqlite, || explain_(user_by_email())).query_plan(&connection).unwrap();
+    assert!(plan.scans("User"));
+
+    let index = with_dialect(Dialect::Sqlite, || create_index_::<User, _>("user_email", |u| u.email()).to_sql());
+    connection.batch_execute(&index).unwrap();
+
+    let plan = with_dialect(Dialect::Sqlite, || explain_(user_by_email())).query_plan(&connection).unwrap();
+    assert!(plan.uses_index("user_email"), "{}", plan);
+    assert!(!plan.scans("User"));
+}
@@ -0,0 +1,135 @@
+use std::rc::Rc;
+
+use proptest::prelude::*;
+
+use hone::dialect::*;
+use hone::expression::*;
+use hone::format::*;
+use hone::query::*;
+use hone::types::*;
+
+use crate::query::model::*;
+
+fn user_by_library() -> impl ToSql {
+    let a = Query::<InnerJoin<_, _>>::from_by(|q, InnerJoin(a, b): InnerJoin<User, Library>| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        let title = or_(&eq_(b.title(), val_("x".to_string())), &eq_(b.title(), val_("y".to_string())));
+        let q = q.where_(and_(&gt_(&a.user_id(), &val_(1)), &title));
+        let q = q.order_(vec![asc_(a.email())]);
+        q.return_(a.email())
+    });
+
+    select(a.unwrap())
+}
+
+#[test]
+fn test_format() {
+    let q = user_by_library();
+
+    assert_eq!(
+        q.to_sql_with(&FormatOptions::default()),
+        "SELECT User.email\n\
+         FROM User\n\
+         INNER JOIN Library ON User.user_id = Library.library_id\n\
+         WHERE User.user_id > 1 AND (Library.title = 'x' OR Library.title = 'y')\n\
+         ORDER BY User.email ASC"
+    );
+    assert_eq!(
+        q.to_sql_with(&FormatOptions::default().multiline(false)),
+        "SELECT User.email FROM User INNER JOIN Library ON User.user_id = Library.library_id \
+         WHERE User.user_id > 1 AND (Library.title = 'x' OR Library.title = 'y') ORDER BY User.email ASC"
+    );
+    assert_eq!(
+        q.to_sql_with(&FormatOptions::default().strip_parens(false)),
+        "SELECT User.email\n\
+         FROM User\n\
+         INNER JOIN Library ON (User.user_id = Library.library_id)\n\
+         WHERE ((User.user_id > 1) AND ((Library.title = 'x') OR (Library.title = 'y')))\n\
+         ORDER BY User.email ASC"
+    );
+}
+
+#[test]
+fn test_format_subquery() {
+    let a = Query::<User>::from_by(|q, a| {
+        let ids = Query::<Library>::from_by(|q, l| {
+            let q = q.where_(like_escape_(l.title(), val_("(a) AND b%".to_string()), '\\'));
+            q.return_(l.library_id())
+        });
+        q.where_(not_(in_sub_(a.user_id(), ids.unwrap()))).return_(a.user_id())
+    });
+    let q = select(a.unwrap());
+
+    assert_eq!(
+        q.to_sql_with(&FormatOptions::default().indent(4)),
+        q.to_sql_with(&FormatOptions::default()).replace("\n  ", "\n    ")
+    );
+    assert_eq!(
+        q.to_sql_with(&FormatOptions::default()),
+        "SELECT User.user_id\n\
+         FROM User\n\
+         WHERE NOT User.user_id IN (\n  \
+         SELECT Library.library_id\n  \
+         FROM Library\n  \
+         WHERE Library.title LIKE '(a) AND b%' ESCAPE '\\'\n\
+         )"
+    );
+}
+
+#[test]
+fn test_format_statements() {
+    let sql_ = "WITH a AS (SELECT (1 + 2) * 3 AS n) SELECT ((a.n - (1 - 2)) * -(4)) FROM a WHERE (a.n IN (9)); DELETE FROM a";
+
+    assert_eq!(
+        format_sql(sql_, &FormatOptions::default()),
+        "WITH a AS (\n  \
+         SELECT (1 + 2) * 3 AS n\n\
+         )\n\
+         SELECT (a.n - (1 - 2)) * -4\n\
+         FROM a\n\
+         WHERE a.n IN (9);\n\
+         DELETE FROM a"
+    );
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Int(i64),
+    Not(Box<Expr>),
+    Op(&'static str, Box<Expr>, Box<Expr>),
+}
+
+fn render(e: &Expr) -> Rc<dyn HasValue<i64, Output = i64>> {
+    match e {
+        Expr::Int(n) => val_(*n),
+        Expr::Not(a) => never_(not_(render(a)).to_sql()),
+        Expr::Op(op, a, b) => binop_(op, &render(a), &render(b)),
+    }
+}
+
+fn expr() -> impl Strategy<Value = Expr> {
+    let ops = [" + ", " - ", " * ", " || ", " = ", " < ", " <> ", " AND ", " OR "];
+
+    (-3i64..3).prop_map(Expr::Int).prop_recursive(5, 32, 2, move |e| {
+        prop_oneof![
+            e.clone().prop_map(|a| Expr::Not(Box::new(a))),
+            (proptest::sample::select(ops.to_vec()), e.clone(), e).prop_map(|(op, a, b)| Expr::Op(op, Box::new(a), Box::new(b))),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_format_semantics_sqlite(e in expr()) {
+        use diesel::dsl::sql;
+        use diesel::prelude::*;
+        use diesel::sql_types::Text;
+
+        let connection = SqliteConnection::establish(":memory:").unwrap();
+        let a = with_dialect(Dialect::Sqlite, || render(&e).to_sql());
+        let b = format_sql(&a, &FormatOptions::default());
+        let eval = |s: &str| diesel::select(sql::<Text>(&format!("CAST(({}) AS TEXT)", s))).get_result::<String>(&connection).unwrap();
+
+        prop_assert_eq!(eval(&a), eval(&b), "{} => {}", a, b);
+    }
+}
@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::query::*;
+use hone::types::*;
+
+use crate::query::model::*;
+
+#[test]
+fn test_coalesce() {
+    let a = Query::<User>::from_by(|q, a| {
+        let empty = val_("".to_string());
+        let none = val_("none".to_string());
+        let email = coalesce_(nullif_(a.email(), empty), none);
+
+        q.return_(email.as_("email"))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT COALESCE(NULLIF(User.email, ''), 'none') AS email FROM User".to_string()
+    );
+}
+
+#[test]
+fn test_coalesce_nullable_column() {
+    let a = Query::<Account>::from_by(|q, a| q.return_(coalesce_(a.nickname(), val_("-".to_string())).as_("nickname")));
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT COALESCE(Account.nickname, '-') AS nickname FROM Account".to_string()
+    );
+}
+
+#[test]
+fn test_greatest_least() {
+    let u = User::default();
+
+    let greatest = greatest_(u.user_id(), val_(1));
+    let least = least_(u.user_id(), val_(1));
+
+    assert_eq!("GREATEST(User.user_id, 1)", greatest.to_sql());
+    assert_eq!("LEAST(User.user_id, 1)", least.to_sql());
+
+    with_dialect(Dialect::Sqlite, || {
+        let greatest = greatest_(u.user_id(), val_(1));
+        let least = least_(u.user_id(), val_(1));
+
+        assert_eq!("MAX(User.user_id, 1)", greatest.to_sql());
+        assert_eq!("MIN(User.user_id, 1)", least.to_sql());
+    });
+}
+
+#[test]
+fn test_cast() {
+    let u = User::default();
+
+    let id: Rc<dyn HasValue<String, Output = Column>> = cast_::<String>(u.user_id());
+    let eq = eq_(id, val_("1".to_string()));
+
+    assert_eq!("(CAST(User.user_id AS VARCHAR) = '1')", eq.to_string());
+
+    let cast = |d| with_dialect(d, || cast_::<i64>(u.user_id()).to_sql());
+
+    assert_eq!("CAST(User.user_id AS INTEGER)", cast(Dialect::Sqlite));
+    assert_eq!("CAST(User.user_id AS BIGINT)", cast(Dialect::Postgres));
+    assert_eq!("CAST(User.user_id AS SIGNED)", cast(Dialect::Mysql));
+}
@@ -0,0 +1,88 @@
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::query::*;
+use hone::types::*;
+
+use crate::query::model::*;
+
+#[test]
+fn test_column_parts() {
+    let c = Column::new("public.User.user_id");
+
+    assert_eq!("public.User.user_id", c.name());
+    assert_eq!("public.User.user_id", HasValue::<u32>::to_sql(&c));
+
+    with_quote_policy(QuotePolicy::Always, || {
+        assert_eq!("\"public\".\"User\".\"user_id\"", c.qualified());
+        assert_eq!("\"order\"", Column::new("order").qualified());
+        assert_eq!("\"a\"\"b\"", Column::new("a\"b").qualified());
+    });
+}
+
+#[test]
+fn test_quote_when_needed() {
+    with_quote_policy(QuotePolicy::WhenNeeded, || {
+        assert_eq!("\"User\".user_id", Column::new("User.user_id").qualified());
+        assert_eq!("\"group\".\"order\"", Column::new("group.order").qualified());
+        assert_eq!("t.\"first name\"", Column::new("t.first name").qualified());
+        assert_eq!("t.\"1st\"", Column::new("t.1st").qualified());
+        assert_eq!("t.MixedCase", Column::new("t.MixedCase").qualified());
+
+        with_dialect(Dialect::Postgres, || {
+            assert_eq!("t.\"MixedCase\"", Column::new("t.MixedCase").qualified());
+        });
+    });
+}
+
+#[test]
+fn test_quote_style() {
+    with_quote_policy(QuotePolicy::Always, || {
+        with_dialect(Dialect::Mysql, || {
+            assert_eq!("`User`.`a``b`", Column::new("User.a`b").qualified());
+        });
+
+        set_quote_style(Some(QuoteStyle::Bracket));
+        assert_eq!("[User].[a]]b]", Column::new("User.a]b").qualified());
+        set_quote_style(None);
+    });
+}
+
+#[test]
+fn test_quote_select() {
+    let sql = with_quote_policy(QuotePolicy::Always, || {
+        let a = Query::<User>::from_by(|q, a| {
+            let q = q.where_(eq_(a.user_id(), val_(1)));
+            q.return_((star_::<User>(), a.email().as_("mail")))
+        });
+
+        select(a.unwrap()).to_sql()
+    });
+
+    assert_eq!(
+        "SELECT \"User\".*, \"User\".\"email\" AS \"mail\" FROM \"User\" WHERE (\"User\".\"user_id\" = 1)",
+        sql
+    );
+}
+
+#[test]
+fn test_quote_insert() {
+    let sql = with_quote_policy(QuotePolicy::WhenNeeded, || {
+        let a = Query::<User>::from_by(|q, a| {
+            let q = q.value_(a.user_id_(), val_(1));
+            q.value_(a.email_(), val_("a@b.c".to_string()))
+        });
+
+        insert_into(a.unwrap()).to_sql()
+    });
+
+    assert_eq!("INSERT INTO \"User\"(user_id, email) VALUES (1, 'a@b.c')", sql);
+}
+
+#[test]
+fn test_unquote_ident() {
+    assert_eq!(unquote_ident("main.User"), vec!["main", "User"]);
+    assert_eq!(unquote_ident(r#""main"."a.b""c""#), vec!["main", r#"a.b"c"#]);
+    assert_eq!(unquote_ident("`it``s`"), vec!["it`s"]);
+    assert_eq!(unquote_ident("[a]]b].c"), vec!["a]b", "c"]);
+}
@@ -1,3 +1,4 @@
+use hone::dialect::*;
 use hone::expression::*;
 use hone::query::*;
 
@@ -61,8 +62,8 @@ fn test_bulk_insert() {
     });
 
     assert_eq!(
-        bulk_insert(a.unwrap()).to_sql(),
-        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c') (2, 'd@e.c')".to_string()
+        bulk_insert(a.unwrap()).unwrap().to_sql(),
+        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c'), (2, 'd@e.c')".to_string()
     );
 }
 
@@ -102,9 +103,370 @@ fn test_duplicate() {
     });
 
     assert_eq!(
-        bulk_insert(a.unwrap()).to_sql(),
-        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c') (2, 'd@e.c') \
+        bulk_insert(a.unwrap()).unwrap().to_sql(),
+        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c'), (2, 'd@e.c') \
          ON DUPLICATE KEY UPDATE user_id = 2"
             .to_string()
     );
 }
+
+#[test]
+fn test_on_conflict() {
+    let upsert = || {
+        Query::<User>::from_by(|q, a| {
+            let q = q.value_(a.user_id_(), val_(1));
+            let q = q.value_(a.email_(), val_("a@b.c".to_string()));
+
+            let sets = vec![set_(a.email_(), excluded_(a.email_()))];
+            let cond = not_eq_(a.email_(), excluded_(a.email_()));
+            q.on_conflict_(a.user_id_()).do_update_where_(sets, cond)
+        })
+    };
+
+    assert_eq!(
+        insert_into(upsert().unwrap()).to_sql(),
+        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c') \
+         ON CONFLICT (user_id) DO UPDATE SET email = excluded.email WHERE (email <> excluded.email)"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || insert_into(upsert().unwrap()).to_sql()),
+        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c') \
+         ON DUPLICATE KEY UPDATE email = IF((email <> VALUES(email)), VALUES(email), email)"
+            .to_string()
+    );
+
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.value_(a.user_id_(), val_(1));
+        q.on_conflict_constraint_("user_pkey").do_nothing_()
+    });
+
+    assert_eq!(
+        insert_into(a.unwrap()).to_sql(),
+        "INSERT INTO User(user_id) VALUES (1) ON CONFLICT ON CONSTRAINT user_pkey DO NOTHING".to_string()
+    );
+}
+
+#[test]
+fn test_on_conflict_bulk() {
+    let upsert = || {
+        Query::<User>::from_by(|q, a| {
+            let rows = vec![(val_(1), val_("a@b.c".to_string())), (val_(2), val_("d@e.c".to_string()))];
+            let q = q.values_((a.user_id_(), a.email_()), rows);
+            q.on_conflict_((a.user_id_(), a.email_())).do_nothing_()
+        })
+    };
+
+    assert_eq!(
+        bulk_insert(upsert().unwrap()).unwrap().to_sql(),
+        "INSERT INTO User(user_id, email) VALUES (1, 'a@b.c'), (2, 'd@e.c') \
+         ON CONFLICT (user_id, email) DO NOTHING"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || bulk_insert(upsert().unwrap()).unwrap().to_sql()),
+        "INSERT IGNORE INTO User(user_id, email) VALUES (1, 'a@b.c'), (2, 'd@e.c')".to_string()
+    );
+}
+
+#[test]
+fn test_on_conflict_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute("CREATE TABLE User (user_id INTEGER PRIMARY KEY, email TEXT); INSERT INTO User VALUES (1, 'a'), (2, 'b');")
+        .unwrap();
+
+    let upsert = |id: u32, email: &str| {
+        with_dialect(Dialect::Sqlite, || {
+            let a = Query::<User>::from_by(|q, a| {
+                let q = q.value_(a.user_id_(), val_(id));
+                let q = q.value_(a.email_(), val_(email.to_string()));
+
+                let sets = vec![set_(a.email_(), excluded_(a.email_()))];
+                q.on_conflict_(a.user_id_()).do_update_where_(sets, eq_(a.user_id_(), val_(1)))
+            });
+            insert_into(a.unwrap()).to_sql()
+        })
+    };
+
+    connection.batch_execute(&upsert(1, "x")).unwrap();
+    connection.batch_execute(&upsert(2, "y")).unwrap();
+    connection.batch_execute(&upsert(3, "z")).unwrap();
+
+    let rows = sql::<Text>("(SELECT group_concat(user_id || email) FROM (SELECT * FROM User ORDER BY user_id))");
+    assert_eq!("1x,2b,3z", diesel::select(rows).get_result::<String>(&connection).unwrap());
+}
+
+#[test]
+fn test_insert_returning() {
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.value_(a.email_(), val_("a@b.c".to_string()));
+        q.returning_(a.user_id_()).unwrap()
+    });
+
+    assert_eq!(
+        insert_into(a.unwrap()).to_sql(),
+        "INSERT INTO User(email) VALUES ('a@b.c') RETURNING user_id".to_string()
+    );
+}
+
+#[test]
+fn test_returning_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    let version = diesel::select(sql::<Text>("sqlite_version()")).get_result::<String>(&connection).unwrap();
+    let version = version.split('.').map(|v| v.parse::<u32>().unwrap()).collect::<Vec<_>>();
+    if version < vec![3, 35] {
+        return;
+    }
+
+    connection
+        .batch_execute("CREATE TABLE User (user_id INTEGER PRIMARY KEY, email TEXT); INSERT INTO User VALUES (7, 'a');")
+        .unwrap();
+
+    let stmt = with_dialect(Dialect::Sqlite, || {
+        let a = Query::<User>::from_by(|q, a| {
+            let q = q.value_(a.email_(), val_("b".to_string()));
+            q.returning_(a.user_id_()).unwrap()
+        });
+        insert_into(a.unwrap()).to_sql()
+    });
+
+    connection.batch_execute(&stmt).unwrap();
+
+    let rows = sql::<Text>("(SELECT group_concat(user_id || email) FROM User)");
+    assert_eq!("7a,8b", diesel::select(rows).get_result::<String>(&connection).unwrap());
+}
+
+fn account(name: &str, nickname: Option<&str>) -> Account {
+    Account {
+        account_id: 0,
+        name: name.to_string(),
+        nickname: nickname.map(|n| n.to_string()),
+    }
+}
+
+#[test]
+fn test_insert_values() {
+    let mut a = account("a", Some("b"));
+    a.account_id = 42;
+
+    let sql = insert_into_values(&a).to_sql();
+    assert!(!sql.contains(&a.account_id.to_string()));
+    assert_eq!(sql, "INSERT INTO Account(name, nickname) VALUES ('a', 'b')".to_string());
+    assert_eq!(
+        insert_into_values(&account("a", None)).to_sql(),
+        "INSERT INTO Account(name, nickname) VALUES ('a', DEFAULT)".to_string()
+    );
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || insert_into_values(&account("a", None)).to_sql()),
+        "INSERT INTO Account(name) VALUES ('a')".to_string()
+    );
+
+    let a = Query::<Account>::from_by(|q, a| q.entity_value_(&account("a", None)).returning_(a.account_id()).unwrap());
+
+    assert_eq!(
+        insert_into(a.unwrap()).to_sql(),
+        "INSERT INTO Account(name, nickname) VALUES ('a', DEFAULT) RETURNING Account.account_id".to_string()
+    );
+}
+
+#[test]
+fn test_bulk_insert_values() {
+    let rows = vec![account("a", Some("b")), account("c", None)];
+
+    assert_eq!(
+        bulk_insert_values(&rows).unwrap().to_sql(),
+        "INSERT INTO Account(name, nickname) VALUES ('a', 'b'), ('c', DEFAULT)".to_string()
+    );
+
+    let rows = vec![account("a", None), account("c", None)];
+
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || bulk_insert_values(&rows).unwrap().to_sql()),
+        "INSERT INTO Account(name) VALUES ('a'), ('c')".to_string()
+    );
+}
+
+#[test]
+fn test_bulk_insert_values_sqlite_default() {
+    let rows = vec![account("a", Some("b")), account("c", None), account("d", Some("e"))];
+
+    let chunked = |limits| {
+        with_dialect(Dialect::Sqlite, || {
+            let q = Query::<Account>::from_by(|q, _| q.entity_values_(&rows)).unwrap();
+            bulk_insert_chunks(q, limits).collect::<Vec<_>>()
+        })
+    };
+
+    // one statement per group of set columns, so to_sql would drop rows
+    assert!(with_dialect(Dialect::Sqlite, || bulk_insert_values(&rows).is_err()));
+    assert_eq!(
+        chunked(ChunkLimits::default()),
+        vec![
+            "INSERT INTO Account(name, nickname) VALUES ('a', 'b'), ('d', 'e')".to_string(),
+            "INSERT INTO Account(name) VALUES ('c')".to_string(),
+        ]
+    );
+    assert_eq!(
+        chunked(ChunkLimits::default().rows(1)),
+        vec![
+            "INSERT INTO Account(name, nickname) VALUES ('a', 'b')".to_string(),
+            "INSERT INTO Account(name, nickname) VALUES ('d', 'e')".to_string(),
+            "INSERT INTO Account(name) VALUES ('c')".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_insert_default_values() {
+    let q = || Query::<Account>::from_by(|q, _| q).unwrap();
+
+    assert_eq!(insert_into(q()).to_sql(), "INSERT INTO Account DEFAULT VALUES".to_string());
+    assert_eq!(with_dialect(Dialect::Mysql, || insert_into(q()).to_sql()), "INSERT INTO Account() VALUES ()".to_string());
+    assert_eq!(
+        insert_into_values(&Visit::default()).to_sql(),
+        "INSERT INTO Visit(note) VALUES (DEFAULT)".to_string()
+    );
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || insert_into_values(&Visit::default()).to_sql()),
+        "INSERT INTO Visit DEFAULT VALUES".to_string()
+    );
+
+    let rows = vec![Visit::default(), Visit { visit_id: 42, note: Some("a".to_string()) }, Visit::default()];
+    let statements = with_dialect(Dialect::Sqlite, || {
+        let q = Query::<Visit>::from_by(|q, _| q.entity_values_(&rows)).unwrap();
+        bulk_insert_chunks(q, ChunkLimits::default()).collect::<Vec<_>>()
+    });
+
+    assert!(with_dialect(Dialect::Sqlite, || bulk_insert_values(&rows).is_err()));
+    assert!(statements.iter().all(|s| !s.contains(&rows[1].visit_id.to_string())));
+    assert_eq!(
+        statements,
+        vec![
+            "INSERT INTO Visit DEFAULT VALUES".to_string(),
+            "INSERT INTO Visit DEFAULT VALUES".to_string(),
+            "INSERT INTO Visit(note) VALUES ('a')".to_string(),
+        ]
+    );
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || bulk_insert_values(&rows).unwrap().to_sql()),
+        "INSERT INTO Visit(note) VALUES (DEFAULT), ('a'), (DEFAULT)".to_string()
+    );
+}
+
+#[test]
+fn test_insert_values_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute("CREATE TABLE Account (account_id INTEGER PRIMARY KEY, name TEXT NOT NULL, nickname TEXT DEFAULT 'anon');")
+        .unwrap();
+
+    with_dialect(Dialect::Sqlite, || {
+        connection.batch_execute(&insert_into_values(&account("a", Some("b"))).to_sql()).unwrap();
+        connection.batch_execute(&insert_into_values(&account("c", None)).to_sql()).unwrap();
+
+        let q = Query::<Account>::from_by(|q, _| q.entity_values_(&[account("d", None), account("e", Some("f"))])).unwrap();
+        for s in bulk_insert_chunks(q, ChunkLimits::default()) {
+            connection.execute(&s).unwrap();
+        }
+    });
+
+    let rows = sql::<Text>("(SELECT group_concat(account_id || name || nickname) FROM Account)");
+    assert_eq!("1ab,2canon,3danon,4ef", diesel::select(rows).get_result::<String>(&connection).unwrap());
+
+    connection
+        .batch_execute("CREATE TABLE Visit (visit_id INTEGER PRIMARY KEY, note TEXT DEFAULT 'none');")
+        .unwrap();
+    with_dialect(Dialect::Sqlite, || {
+        let rows = vec![Visit::default(), Visit { note: Some("a".to_string()), ..Visit::default() }];
+        let q = Query::<Visit>::from_by(|q, _| q.entity_values_(&rows)).unwrap();
+
+        for s in bulk_insert_chunks(q, ChunkLimits::default()) {
+            connection.execute(&s).unwrap();
+        }
+    });
+
+    let rows = sql::<Text>("(SELECT group_concat(visit_id || note) FROM Visit)");
+    assert_eq!("1none,2a", diesel::select(rows).get_result::<String>(&connection).unwrap());
+}
+
+fn accounts(n: usize) -> Vec<Account> {
+    (0..n).map(|i| account(&format!("n{}", i), Some("x"))).collect()
+}
+
+#[test]
+fn test_bulk_insert_chunks() {
+    let rows = accounts(5);
+    let chunked = |limits: ChunkLimits| {
+        let q = Query::<Account>::from_by(|q, _| q.entity_values_(&rows)).unwrap();
+        bulk_insert_chunks(q, limits).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        chunked(ChunkLimits::default().rows(2)),
+        vec![
+            "INSERT INTO Account(name, nickname) VALUES ('n0', 'x'), ('n1', 'x')".to_string(),
+            "INSERT INTO Account(name, nickname) VALUES ('n2', 'x'), ('n3', 'x')".to_string(),
+            "INSERT INTO Account(name, nickname) VALUES ('n4', 'x')".to_string(),
+        ]
+    );
+
+    assert_eq!(chunked(ChunkLimits::default().params(6)).len(), 2);
+    assert_eq!(chunked(ChunkLimits::default().params(1)).len(), 5);
+    assert_eq!(chunked(ChunkLimits::default()).len(), 1);
+
+    // every row renders to the same size, so two rows fit exactly
+    let two = chunked(ChunkLimits::default().rows(2))[0].len();
+    let statements = chunked(ChunkLimits::default().bytes(two));
+    assert_eq!(statements.len(), 3);
+    assert!(statements.iter().all(|s| s.len() <= two));
+    assert_eq!(chunked(ChunkLimits::default().bytes(two - 1)).len(), 5);
+}
+
+#[test]
+fn test_bulk_insert_chunks_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::BigInt;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute("CREATE TABLE Account (account_id INTEGER PRIMARY KEY, name TEXT NOT NULL, nickname TEXT);")
+        .unwrap();
+
+    let rows = accounts(1200);
+    let statements = with_dialect(Dialect::Sqlite, || {
+        let q = Query::<Account>::from_by(|q, _| q.entity_values_(&rows)).unwrap();
+        bulk_insert_chunks(q, ChunkLimits::for_dialect(Dialect::Sqlite).params(SQLITE_LEGACY_MAX_PARAMS)).collect::<Vec<_>>()
+    });
+    assert_eq!(statements.len(), 3);
+
+    connection.transaction::<_, diesel::result::Error, _>(|| {
+        for s in &statements {
+            connection.batch_execute(s)?;
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    let count = diesel::select(sql::<BigInt>("(SELECT COUNT(*) FROM Account)")).get_result::<i64>(&connection);
+    assert_eq!(1200, count.unwrap());
+}
@@ -106,3 +106,56 @@ fn test_nested_inner_join() {
             .to_string()
     );
 }
+
+#[test]
+fn test_join_with_entity() {
+    let a = Query::<(InnerJoin<_, _>, _)>::from_by(|q, (InnerJoin(a, b), c): (InnerJoin<User, Library>, Account)| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        let q = q.where_(eq_(c.name(), a.email()));
+        q.return_((a.email(), c.name()))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.email, Account.name FROM \
+         User INNER JOIN Library ON (User.user_id = Library.library_id),Account \
+         WHERE (Account.name = User.email)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_entity_with_join() {
+    let a = Query::<(_, InnerJoin<_, _>)>::from_by(|q, (c, InnerJoin(a, b)): (Account, InnerJoin<User, Library>)| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        let q = q.where_(eq_(c.name(), a.email()));
+        q.return_((a.email(), c.name()))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.email, Account.name FROM \
+         User INNER JOIN Library ON (User.user_id = Library.library_id),Account \
+         WHERE (Account.name = User.email)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_two_joins() {
+    let a = Query::<(InnerJoin<_, _>, LeftJoin<_, _>)>::from_by(
+        |q, (InnerJoin(a, b), LeftJoin(c, d)): (InnerJoin<User, Library>, LeftJoin<Account, Library>)| {
+            let q = q.on_(eq_(a.user_id(), b.library_id()));
+            let q = q.on_(eq_(c.name(), d.title()));
+            q.return_((a.email(), c.name()))
+        },
+    );
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.email, Account.name FROM \
+         Account LEFT OUTER JOIN Library ON (Account.name = Library.title),\
+         User INNER JOIN Library ON (User.user_id = Library.library_id)"
+            .to_string()
+    );
+}
@@ -0,0 +1,172 @@
+use proptest::prelude::*;
+
+use hone::dialect::*;
+use hone::expression::*;
+
+use crate::query::model::*;
+
+#[test]
+fn test_numeric_literal() {
+    assert_eq!("-128", val_(i8::MIN).to_sql());
+    assert_eq!("9223372036854775807", val_(i64::MAX).to_sql());
+    assert_eq!("18446744073709551615", val_(u64::MAX).to_sql());
+    assert_eq!("1.0", val_(1.0f64).to_sql());
+    assert_eq!("0.25", val_(0.25f32).to_sql());
+
+    let special = |d| with_dialect(d, || vec![val_(f64::NAN).to_sql(), val_(f64::INFINITY).to_sql(), val_(f32::NEG_INFINITY).to_sql()]);
+
+    assert_eq!(special(Dialect::Postgres), vec!["'NaN'::float8", "'Infinity'::float8", "'-Infinity'::float4"]);
+    assert_eq!(special(Dialect::Sqlite), vec!["NULL", "9e999", "-9e999"]);
+    assert_eq!(special(Dialect::Mysql), vec!["NULL", "NULL", "NULL"]);
+
+    // MySQL can't write any of them, both infinities become NULL like NaN
+    let infinities = with_dialect(Dialect::Mysql, || {
+        vec![val_(f64::INFINITY).to_sql(), val_(f64::NEG_INFINITY).to_sql(), val_(f32::INFINITY).to_sql(), val_(f32::NEG_INFINITY).to_sql()]
+    });
+    assert_eq!(infinities, vec!["NULL", "NULL", "NULL", "NULL"]);
+}
+
+#[test]
+fn test_text_literal() {
+    let u = User::default();
+
+    assert_eq!("'a'", val_('a').to_sql());
+    assert_eq!("'abc'", val_("abc").to_sql());
+
+    let eq = eq_(u.email(), val_("a@b.c".to_string()));
+    assert_eq!("(User.email = 'a@b.c')", eq.to_string());
+}
+
+#[test]
+fn test_option_literal() {
+    assert_eq!("NULL", val_(None::<i32>).to_sql());
+    assert_eq!("1", val_(Some(1)).to_sql());
+    assert_eq!("'a'", val_(Some("a".to_string())).to_sql());
+
+    let a = coalesce_(val_(None::<u32>), val_(2u32));
+    assert_eq!("COALESCE(NULL, 2)", a.to_sql());
+}
+
+#[test]
+fn test_blob_literal() {
+    let blob = vec![0u8, 1, 0xab, 0xff];
+
+    assert_eq!("X'0001ABFF'", val_(blob.clone()).to_sql());
+    assert_eq!("'\\x0001ABFF'::bytea", with_dialect(Dialect::Postgres, || val_(blob.clone()).to_sql()));
+    assert_eq!("X''", val_(Vec::<u8>::new()).to_sql());
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn test_decimal_literal() {
+    use rust_decimal::Decimal;
+
+    let d = Decimal::new(-12345, 3);
+
+    assert_eq!("-12.345", val_(d).to_sql());
+    assert_eq!("CAST(-12.345 AS DECIMAL(65, 30))", with_dialect(Dialect::Mysql, || cast_::<Decimal>(val_(d)).to_sql()));
+}
+
+#[test]
+fn test_literal_sqlite() {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::{Binary, Double, Nullable};
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+
+    with_dialect(Dialect::Sqlite, || {
+        let blob = diesel::select(sql::<Binary>(&val_(vec![0u8, 1, 0xff]).to_sql())).get_result::<Vec<u8>>(&connection);
+        let inf = diesel::select(sql::<Double>(&val_(f64::INFINITY).to_sql())).get_result::<f64>(&connection);
+        let nan = diesel::select(sql::<Nullable<Double>>(&val_(f64::NAN).to_sql())).get_result::<Option<f64>>(&connection);
+
+        assert_eq!(blob.unwrap(), vec![0u8, 1, 0xff]);
+        assert_eq!(inf.unwrap(), f64::INFINITY);
+        assert_eq!(nan.unwrap(), None);
+    });
+}
+
+#[test]
+fn test_string_escape() {
+    let name = "O'Brien \\ 100%".to_string();
+
+    assert_eq!("'O''Brien \\ 100%'", val_(name.clone()).to_sql());
+    assert_eq!("'O''Brien \\\\ 100%'", with_dialect(Dialect::Mysql, || val_(name.clone()).to_sql()));
+    assert_eq!("'a\\0b'", with_dialect(Dialect::Mysql, || val_("a\0b").to_sql()));
+    assert_eq!("('a' || char(0) || 'b')", with_dialect(Dialect::Sqlite, || val_("a\0b").to_sql()));
+}
+
+#[test]
+fn test_string_nul() {
+    assert_eq!("('a' || CHR(0) || 'b')", with_dialect(Dialect::Postgres, || val_("a\0b").to_sql()));
+    assert_eq!("U&'a\\\\\\0000b'", val_("a\\\0b").to_sql());
+}
+
+#[test]
+fn test_like_escape() {
+    let u = User::default();
+    let pattern = escape_like("50%_off\\", '\\') + "%";
+    let like = like_escape_(u.email(), val_(pattern), '\\');
+
+    assert_eq!("(User.email LIKE '50\\%\\_off\\\\%' ESCAPE '\\')", like.to_string());
+
+    with_dialect(Dialect::Mysql, || {
+        let pattern = escape_like("50%", '!') + "%";
+        let like = ilike_escape_(u.email(), val_(pattern), '!');
+
+        assert_eq!("(LOWER(User.email) LIKE LOWER('50!%%') ESCAPE '!')", like.to_string());
+    });
+
+    with_dialect(Dialect::Postgres, || {
+        let like = ilike_escape_(u.email(), val_("50!%%".to_string()), '!');
+
+        assert_eq!("(User.email ILIKE '50!%%' ESCAPE '!')", like.to_string());
+    });
+}
+
+#[test]
+fn test_ilike_sqlite() {
+    use diesel::prelude::*;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    let like = with_dialect(Dialect::Sqlite, || ilike_escape_(val_("ABC_1".to_string()), val_("abc!_%".to_string()), '!').to_sql());
+
+    assert_eq!(select_text(&connection, &format!("CAST({} AS TEXT)", like)), "1");
+}
+
+fn select_text(connection: &diesel::SqliteConnection, expr: &str) -> String {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    diesel::select(sql::<Text>(expr)).get_result::<String>(connection).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn test_string_roundtrip_sqlite(s in "(?s).*|[\\x00'\\\\a]{0,8}") {
+        use diesel::prelude::*;
+
+        let connection = SqliteConnection::establish(":memory:").unwrap();
+        let literal = with_dialect(Dialect::Sqlite, || val_(s.clone()).to_sql());
+
+        prop_assert_eq!(select_text(&connection, &literal), s);
+    }
+
+    #[test]
+    fn test_like_roundtrip_sqlite(s in "[a-z%_\\\\]{1,8}", rest in "[a-z%_]{0,4}") {
+        use diesel::prelude::*;
+
+        let connection = SqliteConnection::establish(":memory:").unwrap();
+        let (prefix, other) = with_dialect(Dialect::Sqlite, || {
+            let pattern = val_(escape_like(&s, '\\') + "%");
+            let prefix = like_escape_(val_(s.clone() + &rest), pattern.clone(), '\\');
+            let other = like_escape_(val_(format!("#{}", s)), pattern, '\\');
+
+            (prefix.to_sql(), other.to_sql())
+        });
+
+        prop_assert_eq!(select_text(&connection, &format!("CAST({} AS TEXT)", prefix)), "1");
+        prop_assert_eq!(select_text(&connection, &format!("CAST({} AS TEXT)", other)), "0");
+    }
+}
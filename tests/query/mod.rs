@@ -1,9 +1,22 @@
 mod model;
 
+mod datetime_expr;
+mod ddl_expr;
 mod delete_expr;
+mod explain_expr;
+mod format_expr;
+mod function_expr;
+mod identifier_expr;
 mod insert_expr;
 mod join_expr;
+mod literal_expr;
 mod order_expr;
+mod row_expr;
+mod schema_expr;
+mod scope_expr;
 mod select_expr;
 mod set_expr;
+mod subquery_expr;
+mod time_expr;
+mod tuple_expr;
 mod where_expr;
@@ -3,7 +3,7 @@ use std::rc::Rc;
 use hone::entity::*;
 use hone::query::*;
 use hone::types::*;
-use hone::{hone_entity, hone_model};
+use hone::{hone_entity, hone_insertable, hone_model, hone_table};
 
 #[derive(Debug, Default, Clone)]
 pub struct User {}
@@ -33,7 +33,33 @@ impl HasQuery for User {
 hone_entity!(User, User, email, user_id);
 
 #[derive(Debug, Default, Clone)]
-pub struct Library();
+pub struct Library {
+    pub library_id: u32,
+    pub title: String,
+}
 
 hone_model!(Library, Library, library_id => u32, title => String);
 hone_entity!(Library, Library, library_id, title);
+hone_table!(Library, library_id [primary_key], title);
+
+#[derive(Debug, Default, Clone)]
+pub struct Account {
+    pub account_id: i64,
+    pub name: String,
+    pub nickname: Option<String>,
+}
+
+hone_model!(Account, Account, account_id => i64, name => String, nickname => String);
+hone_entity!(Account, Account, account_id, name, nickname);
+hone_insertable!(Account, auto(account_id), name, nickname);
+hone_table!(Account, account_id [primary_key, auto_increment], name [unique, default("")], nickname);
+
+#[derive(Debug, Default, Clone)]
+pub struct Visit {
+    pub visit_id: i64,
+    pub note: Option<String>,
+}
+
+hone_model!(Visit, Visit, visit_id => i64, note => String);
+hone_entity!(Visit, Visit, visit_id, note);
+hone_insertable!(Visit, auto(visit_id), note);
@@ -0,0 +1,78 @@
+use hone::dialect::*;
+use hone::expression::*;
+use hone::query::*;
+
+use crate::query::model::*;
+
+#[test]
+fn test_row_eq() {
+    let a = Query::<User>::from_by(|q, a| {
+        let key = row_((a.user_id(), a.email()));
+        q.where_(eq_(key, val_((1u32, "a@b.c".to_string()))))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT email, user_id FROM User WHERE ((User.user_id, User.email) = (1, 'a@b.c'))".to_string()
+    );
+}
+
+#[test]
+fn test_row_in() {
+    let a = Query::<User>::from_by(|q, a| {
+        let key = row_((a.user_id(), a.email()));
+        let keys = val_list_(&[val_((1u32, "a".to_string())), val_((3u32, "c".to_string()))]);
+        q.where_(in_(key, keys))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT email, user_id FROM User WHERE ((User.user_id, User.email) IN ((1, 'a'), (3, 'c')))".to_string()
+    );
+}
+
+#[test]
+fn test_row_keyset() {
+    let a = Query::<User>::from_by(|q, a| {
+        let key = row_((a.email(), a.user_id()));
+        let last = row_((val_("b".to_string()), val_(2u32)));
+        let q = q.where_(gt_(&key, &last));
+        let q = q.order_(vec![asc_(a.email()), asc_(a.user_id())]);
+        q.limit_(2)
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT email, user_id FROM User WHERE ((User.email, User.user_id) > ('b', 2)) ORDER BY User.email ASC, User.user_id ASC LIMIT 2"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_row_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE User (email TEXT, user_id INTEGER);
+             INSERT INTO User VALUES ('a', 1), ('b', 2), ('b', 3), ('c', 1);",
+        )
+        .unwrap();
+
+    let stmt = with_dialect(Dialect::Sqlite, || {
+        let a = Query::<User>::from_by(|q, a| {
+            let key = row_((a.email(), a.user_id()));
+            let last = row_((val_("b".to_string()), val_(2u32)));
+            let q = q.where_(gt_(&key, &last));
+            q.return_(a.user_id())
+        });
+        select(a.unwrap()).to_sql()
+    });
+    let expr = format!("(SELECT group_concat(user_id) FROM ({}))", stmt);
+
+    assert_eq!("3,1", diesel::select(sql::<Text>(&expr)).get_result::<String>(&connection).unwrap());
+}
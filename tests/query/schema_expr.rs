@@ -0,0 +1,254 @@
+use hone::dialect::*;
+use hone::query::*;
+use hone::schema::*;
+
+use crate::query::model::*;
+
+fn account_schema() -> Schema {
+    let index = create_index_::<Account, _>("account_nickname", |a| a.nickname());
+
+    Schema::new().table(create_table::<Account>().schema()).index(index.schema())
+}
+
+// Account before `nickname` was added and `name` got its default
+fn old_account_schema() -> Schema {
+    let mut old = account_schema();
+    let table = &mut old.tables[0];
+
+    table.columns.retain(|c| c.name != "nickname");
+    table.columns[1].nullable = true;
+    table.columns[1].default = None;
+    table.columns.push(ColumnSchema {
+        name: "legacy".to_string(),
+        sql_type: "TEXT".to_string(),
+        nullable: true,
+        default: None,
+        primary_key: false,
+        unique: false,
+        auto_increment: false,
+    });
+
+    old.indexes = vec![IndexSchema {
+        name: "account_legacy".to_string(),
+        table: "Account".to_string(),
+        sql: "CREATE INDEX account_legacy ON Account (legacy)".to_string(),
+    }];
+
+    old
+}
+
+#[test]
+fn test_diff_create_drop() {
+    with_dialect(Dialect::Postgres, || {
+        let empty = Schema::new();
+        let schema = account_schema();
+
+        assert_eq!(
+            empty.diff(&schema).unwrap(),
+            vec![
+                "CREATE TABLE Account (account_id BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \
+                 name TEXT NOT NULL DEFAULT '' UNIQUE, nickname TEXT)"
+                    .to_string(),
+                "CREATE INDEX account_nickname ON Account (nickname)".to_string(),
+            ]
+        );
+        assert_eq!(schema.diff(&empty).unwrap(), vec!["DROP TABLE Account".to_string()]);
+        assert!(schema.diff(&account_schema()).unwrap().is_empty());
+    });
+}
+
+#[test]
+fn test_diff_alter() {
+    let diff = |d| with_dialect(d, || old_account_schema().diff(&account_schema()).unwrap());
+
+    assert_eq!(
+        diff(Dialect::Postgres),
+        vec![
+            "DROP INDEX account_legacy".to_string(),
+            "ALTER TABLE Account DROP COLUMN legacy".to_string(),
+            "ALTER TABLE Account ALTER COLUMN name SET NOT NULL".to_string(),
+            "ALTER TABLE Account ALTER COLUMN name SET DEFAULT ''".to_string(),
+            "ALTER TABLE Account ADD COLUMN nickname TEXT".to_string(),
+            "CREATE INDEX account_nickname ON Account (nickname)".to_string(),
+        ]
+    );
+
+    assert_eq!(
+        diff(Dialect::Mysql),
+        vec![
+            "DROP INDEX account_legacy ON Account".to_string(),
+            "ALTER TABLE Account DROP COLUMN legacy".to_string(),
+            "ALTER TABLE Account MODIFY COLUMN name VARCHAR(255) NOT NULL DEFAULT ''".to_string(),
+            "ALTER TABLE Account ADD COLUMN nickname VARCHAR(255)".to_string(),
+            "CREATE INDEX account_nickname ON Account (nickname)".to_string(),
+        ]
+    );
+
+    assert_eq!(
+        diff(Dialect::Sqlite),
+        vec![
+            "DROP INDEX account_legacy".to_string(),
+            "CREATE TABLE _new_Account (account_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, \
+             name TEXT NOT NULL DEFAULT '' UNIQUE, nickname TEXT)"
+                .to_string(),
+            "INSERT INTO _new_Account (account_id, name) SELECT account_id, name FROM Account".to_string(),
+            "DROP TABLE Account".to_string(),
+            "ALTER TABLE _new_Account RENAME TO Account".to_string(),
+            "CREATE INDEX account_nickname ON Account (nickname)".to_string(),
+        ]
+    );
+}
+
+// Account with a composite key, `name` UNIQUE and the named check swapped for another
+fn keyed_account_schemas() -> (Schema, Schema) {
+    let mut old = account_schema();
+    old.tables[0].columns[1].unique = false;
+    old.tables[0].constraints = vec!["CONSTRAINT named CHECK (name <> '')".to_string()];
+
+    let mut new = account_schema();
+    new.tables[0].columns[1].primary_key = true;
+    new.tables[0].constraints = vec!["CHECK (account_id > 0)".to_string()];
+
+    (old, new)
+}
+
+#[test]
+fn test_diff_keys() {
+    let diff = |d| {
+        with_dialect(d, || {
+            let (old, new) = keyed_account_schemas();
+            old.diff(&new)
+        })
+    };
+
+    assert_eq!(
+        diff(Dialect::Postgres),
+        Ok(vec![
+            "ALTER TABLE Account DROP CONSTRAINT named".to_string(),
+            "ALTER TABLE Account DROP CONSTRAINT Account_pkey".to_string(),
+            "ALTER TABLE Account ADD PRIMARY KEY (account_id, name)".to_string(),
+            "ALTER TABLE Account ADD UNIQUE (name)".to_string(),
+            "ALTER TABLE Account ADD CHECK (account_id > 0)".to_string(),
+        ])
+    );
+
+    assert_eq!(
+        diff(Dialect::Mysql),
+        Ok(vec![
+            "ALTER TABLE Account DROP CONSTRAINT named".to_string(),
+            "ALTER TABLE Account DROP PRIMARY KEY, ADD PRIMARY KEY (account_id, name)".to_string(),
+            "ALTER TABLE Account ADD UNIQUE (name)".to_string(),
+            "ALTER TABLE Account ADD CHECK (account_id > 0)".to_string(),
+        ])
+    );
+
+    // the database named the unnamed check, so it cannot be dropped
+    let back = |d| {
+        with_dialect(d, || {
+            let (old, new) = keyed_account_schemas();
+            new.diff(&old)
+        })
+    };
+    assert_eq!(back(Dialect::Postgres), Err(()));
+    assert_eq!(back(Dialect::Mysql), Err(()));
+    assert!(back(Dialect::Sqlite).is_ok());
+}
+
+#[test]
+fn test_diff_auto_increment() {
+    let mut old = with_dialect(Dialect::Postgres, account_schema);
+    old.tables[0].columns[0].auto_increment = false;
+    old.tables[0].columns[1].unique = false;
+
+    assert_eq!(
+        old.diff(&with_dialect(Dialect::Postgres, account_schema)),
+        Ok(vec![
+            "ALTER TABLE Account ALTER COLUMN account_id ADD GENERATED BY DEFAULT AS IDENTITY".to_string(),
+            "ALTER TABLE Account ADD UNIQUE (name)".to_string(),
+        ])
+    );
+    assert_eq!(
+        with_dialect(Dialect::Postgres, account_schema).diff(&old),
+        Ok(vec![
+            "ALTER TABLE Account DROP CONSTRAINT Account_name_key".to_string(),
+            "ALTER TABLE Account ALTER COLUMN account_id DROP IDENTITY".to_string(),
+        ])
+    );
+
+    let mut old = with_dialect(Dialect::Mysql, account_schema);
+    old.tables[0].columns[0].auto_increment = false;
+    old.tables[0].columns[1].unique = false;
+
+    assert_eq!(
+        with_dialect(Dialect::Mysql, account_schema).diff(&old),
+        Ok(vec![
+            "ALTER TABLE Account MODIFY COLUMN account_id BIGINT NOT NULL".to_string(),
+            "ALTER TABLE Account DROP INDEX name".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn test_diff_rebuild_not_null() {
+    with_dialect(Dialect::Sqlite, || {
+        let mut new = account_schema();
+        new.tables[0].columns[1].unique = false;
+        new.tables[0].columns.push(ColumnSchema {
+            name: "rank".to_string(),
+            sql_type: "INTEGER".to_string(),
+            nullable: false,
+            default: None,
+            primary_key: false,
+            unique: false,
+            auto_increment: false,
+        });
+
+        // the copied rows would have no value for `rank`
+        assert_eq!(account_schema().diff(&new), Err(()));
+
+        new.tables[0].columns[3].default = Some("0".to_string());
+        assert!(account_schema().diff(&new).is_ok());
+    });
+}
+
+#[test]
+fn test_diff_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+
+    with_dialect(Dialect::Sqlite, || {
+        let old = old_account_schema();
+        let new = account_schema();
+
+        for stmt in Schema::new().diff(&old).unwrap() {
+            connection.batch_execute(&stmt).unwrap();
+        }
+        connection.batch_execute("INSERT INTO Account (name, legacy) VALUES ('a', 'x'), ('b', 'y')").unwrap();
+
+        for stmt in old.diff(&new).unwrap() {
+            connection.batch_execute(&stmt).unwrap();
+        }
+        connection.batch_execute("INSERT INTO Account (nickname) VALUES ('c')").unwrap();
+
+        let rows = sql::<Text>("(SELECT group_concat(account_id || name || coalesce(nickname, '-')) FROM Account)");
+        assert_eq!("1a-,2b-,3c", diesel::select(rows).get_result::<String>(&connection).unwrap());
+
+        let index = sql::<Text>("(SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'Account' AND sql IS NOT NULL)");
+        assert_eq!("account_nickname", diesel::select(index).get_result::<String>(&connection).unwrap());
+    });
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_snapshot() {
+    let schema = with_dialect(Dialect::Sqlite, account_schema);
+    let snapshot = serde_json::to_string_pretty(&schema).unwrap();
+    let restored: Schema = serde_json::from_str(&snapshot).unwrap();
+
+    assert_eq!(schema, restored);
+    assert!(restored.diff(&schema).unwrap().is_empty());
+}
@@ -0,0 +1,86 @@
+use hone::expression::*;
+use hone::query::*;
+
+use crate::query::model::*;
+
+fn active_users(q: Query<User>) -> Query<User> {
+    let u = q.value.clone();
+    q.where_(is_not_null_(u.email()))
+}
+
+fn staff(q: Query<User>) -> Query<User> {
+    let u = q.value.clone();
+    q.where_(lt_(&u.user_id(), &val_(100)))
+}
+
+fn by_email(q: Query<User>) -> Query<User> {
+    let u = q.value.clone();
+    q.order_(vec![asc_(u.email())])
+}
+
+#[test]
+fn test_scope() {
+    let a = Query::<User>::from_by(|q, a| q.scope_(active_users).return_(a.user_id()));
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.user_id FROM User WHERE (User.email IS NOT NULL)".to_string()
+    );
+}
+
+#[test]
+fn test_scope_compose() {
+    let active_staff = scope_(active_users).and_(staff);
+    let listing = active_staff.clone().and_(by_email);
+
+    let count = Query::<User>::from_by(|q, _| q.scope_(active_staff.clone()).return_(count_rows_()));
+    let page = Query::<User>::from_by(|q, a| q.scope_(listing.clone()).limit_(10).return_(a.email()));
+
+    assert_eq!(
+        select(count.unwrap()).to_sql(),
+        "SELECT COUNT(*) FROM User WHERE ((User.email IS NOT NULL) AND (User.user_id < 100))".to_string()
+    );
+    assert_eq!(
+        select(page.unwrap()).to_sql(),
+        "SELECT User.email FROM User WHERE ((User.email IS NOT NULL) AND (User.user_id < 100)) ORDER BY User.email ASC LIMIT 10".to_string()
+    );
+}
+
+#[test]
+fn test_merge() {
+    let u = User::default();
+    let base = Query::<User>::from_by(|q, a| q.where_(gt_(&a.user_id(), &val_(1))).order_(vec![asc_(a.email())]).limit_(10)).unwrap();
+    let page = Query::<User>::from_by(|q, a| {
+        let q = q.where_(is_not_null_(a.email())).order_(vec![desc_(a.user_id())]);
+        q.group_by_(a.email()).having_(gt_(&count_rows_(), &val_(1))).limit_(20).offset_(40)
+    })
+    .unwrap();
+
+    assert_eq!(
+        select(base.clone().merge_(&page).return_(u.email())).to_sql(),
+        "SELECT User.email FROM User WHERE ((User.user_id > 1) AND (User.email IS NOT NULL)) \
+         GROUP BY User.email HAVING (COUNT(*) > 1) ORDER BY User.email ASC, User.user_id DESC LIMIT 20 OFFSET 40"
+            .to_string()
+    );
+
+    let libraries = Query::<Library>::from_().unwrap();
+    assert_eq!(
+        select(base.merge_(&libraries).return_(u.email())).to_sql(),
+        "SELECT User.email FROM Library,User WHERE (User.user_id > 1) ORDER BY User.email ASC LIMIT 10".to_string()
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_merge_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::prelude::*;
+
+    let u = User::default();
+    let base = Query::<User>::from_by(|q, a| q.order_(vec![asc_(a.email())])).unwrap();
+    let page = Query::<User>::from_by(|q, a| q.group_by_(a.email()).having_(gt_(&count_rows_(), &val_(1))).limit_(20)).unwrap();
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection.batch_execute("CREATE TABLE User (email TEXT, user_id INTEGER)").unwrap();
+    connection.batch_execute(&select(base.merge_(&page).return_(u.email())).to_sql()).unwrap();
+}
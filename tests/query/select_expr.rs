@@ -19,6 +19,27 @@ fn test_select() {
     );
 }
 
+#[test]
+fn test_fork() {
+    let base = Query::<User>::from_by(|q, a| q.where_(gt_(&a.user_id(), &val_(1)))).unwrap();
+    let u = User::default();
+
+    let page = base.clone().order_(vec![asc_(u.email())]).limit_(10).offset_(20).return_(u.email());
+    let count = base.clone().return_(count_rows_());
+    let export = base.clone().where_(is_not_null_(u.email())).return_(u.email());
+
+    assert_eq!(
+        select(page).to_sql(),
+        "SELECT User.email FROM User WHERE (User.user_id > 1) ORDER BY User.email ASC LIMIT 10 OFFSET 20".to_string()
+    );
+    assert_eq!(select(count).to_sql(), "SELECT COUNT(*) FROM User WHERE (User.user_id > 1)".to_string());
+    assert_eq!(
+        select(export).to_sql(),
+        "SELECT User.email FROM User WHERE ((User.user_id > 1) AND (User.email IS NOT NULL))".to_string()
+    );
+    assert_eq!(select(base.return_(u.user_id())).to_sql(), "SELECT User.user_id FROM User WHERE (User.user_id > 1)".to_string());
+}
+
 #[test]
 fn test_star() {
     let a = Query::<User>::from_by(|q, a| {
@@ -1,3 +1,4 @@
+use hone::dialect::*;
 use hone::expression::*;
 use hone::query::*;
 use hone::types::*;
@@ -21,7 +22,7 @@ fn test_set() {
     });
 
     assert_eq!(
-        update(a.unwrap()).to_sql(),
+        update(a.unwrap()).unwrap().to_sql(),
         "UPDATE User SET User.user_id = 1, User.email = 'd@e.f' \
          WHERE (User.email = 'a@b.c')"
             .to_string()
@@ -48,7 +49,7 @@ fn test_set_join() {
     });
 
     assert_eq!(
-        update(a.unwrap()).to_sql(),
+        update(a.unwrap()).unwrap().to_sql(),
         "UPDATE User INNER JOIN Library ON (User.user_id = Library.library_id) SET User.user_id = 1, User.email = 'd@e.f' \
          WHERE (User.email = 'a@b.c')"
             .to_string()
@@ -81,3 +82,161 @@ fn test_update_select() {
             .to_string()
     );
 }
+
+#[test]
+fn test_set_returning() {
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.where_(eq_(a.user_id(), val_(1)));
+        let q = q.value_(a.email(), val_("d@e.f".to_string()));
+        q.returning_(star_::<User>()).unwrap()
+    });
+
+    assert_eq!(
+        update(a.unwrap()).unwrap().to_sql(),
+        "UPDATE User SET User.email = 'd@e.f' WHERE (User.user_id = 1) RETURNING User.*".to_string()
+    );
+}
+
+fn update_user_by_library() -> String {
+    let a = Query::<InnerJoin<_, _>>::from_by(|q, InnerJoin(a, b): InnerJoin<User, Library>| {
+        let q = q.on_(eq_(a.user_id(), b.library_id()));
+        let q = q.where_(eq_(b.title(), val_("x".to_string())));
+        let q = q.value_(a.email(), val_("z".to_string()));
+        q.return_(a)
+    });
+
+    update(a.unwrap()).unwrap().to_sql()
+}
+
+fn update_first_users() -> String {
+    let a = Query::<User>::from_by(|q, a| {
+        let q = q.where_(gt_(&a.user_id(), &val_(1)));
+        let q = q.order_(vec![desc_(a.user_id())]);
+        let q = q.limit_(1);
+        q.value_(a.email(), val_("z".to_string()))
+    });
+
+    update(a.unwrap()).unwrap().to_sql()
+}
+
+#[test]
+fn test_set_join_dialect() {
+    assert_eq!(
+        with_dialect(Dialect::Mysql, update_user_by_library),
+        "UPDATE User INNER JOIN Library ON (User.user_id = Library.library_id) SET User.email = 'z' \
+         WHERE (Library.title = 'x')"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Postgres, update_user_by_library),
+        "UPDATE User SET email = 'z' FROM Library WHERE (User.user_id = Library.library_id) AND (Library.title = 'x')".to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, update_user_by_library),
+        "UPDATE User SET email = 'z' FROM Library WHERE (User.user_id = Library.library_id) AND (Library.title = 'x')".to_string()
+    );
+}
+
+#[test]
+fn test_set_order_limit() {
+    assert_eq!(
+        with_dialect(Dialect::Mysql, update_first_users),
+        "UPDATE User SET User.email = 'z' WHERE (User.user_id > 1) ORDER BY User.user_id DESC LIMIT 1".to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Postgres, update_first_users),
+        "UPDATE User SET email = 'z' WHERE (User.user_id > 1) AND User.ctid IN \
+         (SELECT User.ctid FROM User WHERE (User.user_id > 1) ORDER BY User.user_id DESC LIMIT 1)"
+            .to_string()
+    );
+
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, update_first_users),
+        "UPDATE User SET email = 'z' WHERE (User.user_id > 1) AND User.rowid IN \
+         (SELECT User.rowid FROM User WHERE (User.user_id > 1) ORDER BY User.user_id DESC LIMIT 1)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_set_join_order_limit() {
+    let q = || {
+        Query::<InnerJoin<_, _>>::from_by(|q, InnerJoin(a, b): InnerJoin<User, Library>| {
+            let q = q.on_(eq_(a.user_id(), b.library_id()));
+            let q = q.order_(vec![desc_(a.user_id())]);
+            let q = q.limit_(1);
+            let q = q.value_(a.email(), val_("z".to_string()));
+            q.return_(a)
+        })
+        .unwrap()
+    };
+
+    assert!(with_dialect(Dialect::Mysql, || update(q()).is_err()));
+    assert!(update(q()).is_err());
+    assert_eq!(
+        with_dialect(Dialect::Sqlite, || update(q()).unwrap().to_sql()),
+        "UPDATE User SET email = 'z' FROM Library WHERE (User.user_id = Library.library_id) AND User.rowid IN \
+         (SELECT User.rowid FROM User INNER JOIN Library ON (User.user_id = Library.library_id) ORDER BY User.user_id DESC LIMIT 1)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_set_target() {
+    let a = Query::<(_, _)>::from_by(|q, (a, b): (User, Library)| {
+        let q = q.where_(eq_(a.user_id(), b.library_id()));
+        let q = q.value_(a.email(), val_("z".to_string()));
+        q.return_(a)
+    });
+
+    assert_eq!(
+        with_dialect(Dialect::Postgres, || update(a.unwrap()).unwrap().to_sql()),
+        "UPDATE User SET email = 'z' FROM Library WHERE (User.user_id = Library.library_id)".to_string()
+    );
+
+    // no FROM to take the table from
+    assert!(update(Query::new(User::default())).is_err());
+
+    let a = || {
+        Query::<LeftJoin<_, _>>::from_by(|q, LeftJoin(a, b): LeftJoin<User, Library>| {
+            let q = q.on_(eq_(a.user_id(), b.library_id()));
+            let q = q.value_(a.email(), val_("z".to_string()));
+            q.return_(a)
+        })
+        .unwrap()
+    };
+
+    assert!(with_dialect(Dialect::Postgres, || update(a()).is_err()));
+    assert!(with_dialect(Dialect::Sqlite, || update(a()).is_err()));
+    assert_eq!(
+        with_dialect(Dialect::Mysql, || update(a()).unwrap().to_sql()),
+        "UPDATE User LEFT OUTER JOIN Library ON (User.user_id = Library.library_id) SET User.email = 'z'".to_string()
+    );
+}
+
+#[test]
+fn test_set_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE User (email TEXT, user_id INTEGER);
+             CREATE TABLE Library (library_id INTEGER, title TEXT);
+             INSERT INTO User VALUES ('a', 1), ('b', 2), ('c', 3);
+             INSERT INTO Library VALUES (1, 'x'), (2, 'y'), (3, 'y');",
+        )
+        .unwrap();
+
+    connection.batch_execute(&with_dialect(Dialect::Sqlite, update_user_by_library)).unwrap();
+    connection.batch_execute(&with_dialect(Dialect::Sqlite, update_first_users)).unwrap();
+
+    let rows = sql::<Text>("(SELECT group_concat(email) FROM (SELECT email FROM User ORDER BY user_id))");
+    assert_eq!("z,b,z", diesel::select(rows).get_result::<String>(&connection).unwrap());
+}
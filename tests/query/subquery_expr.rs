@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::query::*;
+use hone::types::*;
+
+use crate::query::model::*;
+
+fn library_ids() -> Query<Rc<dyn HasValue<u32, Output = Column>>> {
+    Query::<Library>::from_by(|q, l| q.return_(l.library_id())).unwrap()
+}
+
+#[test]
+fn test_in_sub() {
+    let a = Query::<User>::from_by(|q, a| q.where_(in_sub_(a.user_id(), library_ids())).return_(a.user_id()));
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.user_id FROM User WHERE (User.user_id IN (SELECT Library.library_id FROM Library))".to_string()
+    );
+
+    let a = Query::<User>::from_by(|q, a| q.where_(not_in_sub_(a.user_id(), library_ids())).return_(a.user_id()));
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.user_id FROM User WHERE (User.user_id NOT IN (SELECT Library.library_id FROM Library))".to_string()
+    );
+}
+
+#[test]
+fn test_quantified() {
+    let u = User::default();
+
+    assert_eq!(
+        "(User.user_id = ANY (SELECT Library.library_id FROM Library))",
+        eq_any_(u.user_id(), library_ids()).to_sql()
+    );
+    assert_eq!(
+        "(User.user_id > ALL (SELECT Library.library_id FROM Library))",
+        gt_all_(u.user_id(), library_ids()).to_sql()
+    );
+    assert_eq!(
+        "(User.user_id <= SOME (SELECT Library.library_id FROM Library))",
+        lte_some_(u.user_id(), library_ids()).to_sql()
+    );
+
+    with_dialect(Dialect::Sqlite, || {
+        assert_eq!(
+            "(EXISTS (SELECT 1 FROM (SELECT Library.library_id AS _v FROM Library) WHERE (User.user_id = _v)))",
+            eq_any_(u.user_id(), library_ids()).to_sql()
+        );
+        assert_eq!(
+            "(NOT EXISTS (SELECT 1 FROM (SELECT Library.library_id AS _v FROM Library) WHERE (User.user_id > _v) IS NOT TRUE))",
+            gt_all_(u.user_id(), library_ids()).to_sql()
+        );
+    });
+}
+
+#[test]
+fn test_quantified_sqlite() {
+    use diesel::connection::SimpleConnection;
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    connection
+        .batch_execute(
+            "CREATE TABLE User (email TEXT, user_id INTEGER);
+             CREATE TABLE Library (library_id INTEGER, title TEXT);
+             INSERT INTO User VALUES ('a', 1), ('b', 2), ('c', 3);
+             INSERT INTO Library VALUES (2, 'x'), (3, 'y');",
+        )
+        .unwrap();
+
+    let user_ids = |f: fn(&User) -> Rc<dyn HasValue<bool, Output = bool>>| {
+        let stmt = with_dialect(Dialect::Sqlite, || {
+            let a = Query::<User>::from_by(|q, a| {
+                let cond = f(&a);
+                q.where_(cond).return_(a.user_id())
+            });
+            select(a.unwrap()).to_sql()
+        });
+        let expr = format!("(SELECT COALESCE(group_concat(user_id), '') FROM ({}))", stmt);
+
+        diesel::select(sql::<Text>(&expr)).get_result::<String>(&connection).unwrap()
+    };
+
+    assert_eq!("2,3", user_ids(|u| eq_any_(u.user_id(), library_ids())));
+    assert_eq!("1,2", user_ids(|u| lt_some_(u.user_id(), library_ids())));
+    assert_eq!("3", user_ids(|u| gte_all_(u.user_id(), library_ids())));
+    assert_eq!("1", user_ids(|u| lt_all_(u.user_id(), library_ids())));
+    assert_eq!("1", user_ids(|u| not_in_sub_(u.user_id(), library_ids())));
+}
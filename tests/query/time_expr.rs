@@ -0,0 +1,105 @@
+use std::rc::Rc;
+
+use time::{Date, Duration, Month, PrimitiveDateTime, Time, UtcOffset};
+
+use hone::datetime::DatePart;
+use hone::dialect::*;
+use hone::entity::*;
+use hone::expression::*;
+use hone::types::*;
+
+fn created_at() -> Rc<dyn HasValue<PrimitiveDateTime, Output = Column>> {
+    Rc::new(Column::new("User.created_at"))
+}
+
+fn datetime() -> PrimitiveDateTime {
+    let date = Date::from_calendar_date(2019, Month::January, 2).unwrap();
+    PrimitiveDateTime::new(date, Time::from_hms_micro(3, 4, 5, 600).unwrap())
+}
+
+#[test]
+fn test_time_literal() {
+    let utc = datetime().assume_utc();
+    let tokyo = utc.to_offset(UtcOffset::from_hms(9, 0, 0).unwrap());
+
+    assert_eq!("DATE '2019-01-02'", val_(datetime().date()).to_sql());
+    assert_eq!("TIME '03:04:05.0006'", val_(datetime().time()).to_sql());
+    assert_eq!("TIMESTAMP '2019-01-02 03:04:05.0006'", val_(datetime()).to_sql());
+
+    with_dialect(Dialect::Sqlite, || {
+        assert_eq!("'2019-01-02'", val_(datetime().date()).to_sql());
+        assert_eq!("'2019-01-02 03:04:05.0006+00:00'", val_(utc).to_sql());
+        assert_eq!("'90 seconds'", val_(Duration::seconds(90)).to_sql());
+    });
+
+    with_dialect(Dialect::Postgres, || {
+        assert_eq!("TIMESTAMPTZ '2019-01-02 12:04:05.0006+09:00'", val_(tokyo).to_sql());
+        assert_eq!("INTERVAL '-1.5 seconds'", val_(Duration::milliseconds(-1500)).to_sql());
+    });
+
+    with_dialect(Dialect::Mysql, || {
+        assert_eq!("TIMESTAMP '2019-01-02 03:04:05.0006'", val_(tokyo).to_sql());
+        assert_eq!("INTERVAL 1500 MICROSECOND", val_(Duration::microseconds(1500)).to_sql());
+    });
+}
+
+#[test]
+fn test_time_types() {
+    assert_eq!(<Date as SqlType>::type_name(Dialect::Postgres), "DATE");
+    assert_eq!(<PrimitiveDateTime as SqlType>::type_name(Dialect::Mysql), "DATETIME");
+    assert_eq!(<time::OffsetDateTime as SqlType>::type_name(Dialect::Postgres), "TIMESTAMPTZ");
+    assert_eq!(<Time as SqlType>::type_name(Dialect::Sqlite), "TEXT");
+}
+
+#[test]
+fn test_time_functions() {
+    let render = |d| {
+        with_dialect(d, || {
+            let today: Rc<dyn HasValue<Date, Output = Column>> = now_();
+            let now: Rc<dyn HasValue<PrimitiveDateTime, Output = Column>> = now_();
+            let day = date_trunc_(DatePart::Day, created_at());
+            let later = date_add_(created_at(), val_(Duration::days(1)));
+
+            vec![today.to_sql(), now.to_sql(), day.to_sql(), later.to_sql()]
+        })
+    };
+
+    assert_eq!(
+        render(Dialect::Sqlite),
+        vec![
+            "date('now')",
+            "datetime('now')",
+            "strftime('%Y-%m-%d 00:00:00', User.created_at)",
+            "datetime(User.created_at, '86400 seconds')",
+        ]
+    );
+
+    assert_eq!(
+        render(Dialect::Postgres),
+        vec![
+            "CURRENT_DATE",
+            "NOW()",
+            "date_trunc('day', User.created_at)",
+            "(User.created_at + INTERVAL '86400 seconds')",
+        ]
+    );
+}
+
+#[test]
+fn test_time_sqlite() {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+
+    let connection = SqliteConnection::establish(":memory:").unwrap();
+    let (later, today) = with_dialect(Dialect::Sqlite, || {
+        let later = date_add_(val_(datetime()), val_(Duration::hours(22)));
+        (later.to_sql(), now_::<Date>().to_sql())
+    });
+
+    let later = diesel::select(sql::<Text>(&later)).get_result::<String>(&connection).unwrap();
+    let today = diesel::select(sql::<Text>(&today)).get_result::<String>(&connection).unwrap();
+
+    assert_eq!(later, "2019-01-03 01:04:05");
+    assert_eq!(today.len(), "2019-01-02".len());
+}
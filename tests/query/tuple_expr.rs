@@ -0,0 +1,60 @@
+use hone::expression::*;
+use hone::query::*;
+
+use crate::query::model::*;
+
+#[test]
+fn test_wide_select() {
+    let a = Query::<User>::from_by(|q, a| {
+        let e = || a.email();
+        let u = || a.user_id();
+
+        q.return_((u(), e(), u(), e(), u(), e(), u(), e(), u(), e(), u(), e(), u(), e(), u(), e()))
+    });
+
+    let columns = ["User.user_id, User.email"; 8].join(", ");
+    assert_eq!(select(a.unwrap()).to_sql(), format!("SELECT {} FROM User", columns));
+}
+
+#[test]
+fn test_wide_from() {
+    let a = Query::<(_, _, _)>::from_by(|q, (a, b, c): (User, Library, Account)| {
+        let q = q.where_(eq_(a.user_id(), b.library_id()));
+        q.return_((a.email(), b.title(), c.name()))
+    });
+
+    assert_eq!(
+        select(a.unwrap()).to_sql(),
+        "SELECT User.email, Library.title, Account.name FROM Account,Library,User \
+         WHERE (User.user_id = Library.library_id)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_wide_values() {
+    let a = Query::<User>::from_by(|q, a| {
+        let row = |i: u32| (val_(i), val_(format!("{}@x", i)), val_(i), val_(i), val_(i), val_(i), val_(i), val_(i), val_(i));
+        let columns = (a.user_id_(), a.email_(), a.user_id_(), a.user_id_(), a.user_id_(), a.user_id_(), a.user_id_(), a.user_id_(), a.user_id_());
+
+        q.values_(columns, vec![row(1)])
+    });
+
+    assert_eq!(
+        bulk_insert(a.unwrap()).unwrap().to_sql(),
+        "INSERT INTO User(user_id, email, user_id, user_id, user_id, user_id, user_id, user_id, user_id) \
+         VALUES (1, '1@x', 1, 1, 1, 1, 1, 1, 1)"
+            .to_string()
+    );
+}
+
+#[test]
+fn test_wide_arguments() {
+    let u = User::default();
+    let args = (u.user_id(), u.email(), u.user_id(), u.email(), u.user_id(), u.email(), u.user_id(), u.email());
+
+    assert_eq!(
+        count_(args).to_sql(),
+        "COUNT(User.user_id,User.email,User.user_id,User.email,User.user_id,User.email,User.user_id,User.email)"
+    );
+}
@@ -199,7 +199,7 @@ fn test_where() {
 
     assert_eq!(
         select(e.unwrap()).to_sql(),
-        "SELECT email, user_id FROM User WHERE (User.user_id IN ((1, 2, 3)))".to_string()
+        "SELECT email, user_id FROM User WHERE (User.user_id IN (1, 2, 3))".to_string()
     );
 
     let f = Query::<(User, Library)>::from_by(|q, (a, _b)| {
@@ -214,7 +214,7 @@ fn test_where() {
 
     assert_eq!(
         select(f.unwrap()).to_sql(),
-        "SELECT email, user_id FROM Library,User WHERE (User.user_id IN ((1, 2, 3)))".to_string()
+        "SELECT email, user_id FROM Library,User WHERE (User.user_id IN (1, 2, 3))".to_string()
     );
 
     let g = Query::<User>::from_by(|q, a| {